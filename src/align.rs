@@ -0,0 +1,198 @@
+use crate::models::Monitor;
+
+/// Operation lining up several outputs in the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Tops,
+    Bottoms,
+    Centres,
+    /// Equal gaps between the outputs, keeping the outer ones in place
+    DistributeHorizontally,
+    /// Each output below the previous one, left edges lined up
+    StackVertically,
+    /// Each output right of the previous one with no gaps
+    PackLeftToRight,
+}
+
+impl Alignment {
+    pub const ALL: [Self; 6] = [
+        Self::Tops,
+        Self::Bottoms,
+        Self::Centres,
+        Self::DistributeHorizontally,
+        Self::StackVertically,
+        Self::PackLeftToRight,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Tops => "Align Tops",
+            Self::Bottoms => "Align Bottoms",
+            Self::Centres => "Align Centres",
+            Self::DistributeHorizontally => "Distribute",
+            Self::StackVertically => "Stack",
+            Self::PackLeftToRight => "Pack Left to Right",
+        }
+    }
+}
+
+/// Output of the layout as a rectangle in logical pixels
+struct Placed {
+    idx: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+/// New logical position of each of `monitors` after `alignment`, in the same
+/// order. Outputs without a mode keep their position.
+pub fn align(monitors: &[&Monitor], alignment: Alignment) -> Vec<(i32, i32)> {
+    let mut positions: Vec<(i32, i32)> = monitors.iter().map(|m| (m.x, m.y)).collect();
+    let mut placed: Vec<Placed> = monitors
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, m)| {
+            let (width, height) = m.logical_size()?;
+            Some(Placed {
+                idx,
+                x: m.x as f32,
+                y: m.y as f32,
+                width,
+                height,
+            })
+        })
+        .collect();
+
+    match alignment {
+        Alignment::Tops => {
+            let top = placed.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+            for p in &mut placed {
+                p.y = top;
+            }
+        }
+        Alignment::Bottoms => {
+            let bottom = placed
+                .iter()
+                .map(|p| p.y + p.height)
+                .fold(f32::MIN, f32::max);
+            for p in &mut placed {
+                p.y = bottom - p.height;
+            }
+        }
+        Alignment::Centres => {
+            let top = placed.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+            let bottom = placed
+                .iter()
+                .map(|p| p.y + p.height)
+                .fold(f32::MIN, f32::max);
+            let centre = f32::midpoint(top, bottom);
+            for p in &mut placed {
+                p.y = centre - p.height / 2.0;
+            }
+        }
+        Alignment::DistributeHorizontally => {
+            placed.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let left = placed.iter().map(|p| p.x).fold(f32::MAX, f32::min);
+            let right = placed
+                .iter()
+                .map(|p| p.x + p.width)
+                .fold(f32::MIN, f32::max);
+            let widths: f32 = placed.iter().map(|p| p.width).sum();
+            let gaps = placed.len().saturating_sub(1).max(1) as f32;
+            let gap = (right - left - widths) / gaps;
+            let mut x = left;
+            for p in &mut placed {
+                p.x = x;
+                x += p.width + gap;
+            }
+        }
+        Alignment::StackVertically => {
+            placed.sort_by(|a, b| a.y.total_cmp(&b.y));
+            let left = placed.first().map_or(0.0, |p| p.x);
+            let mut y = placed.first().map_or(0.0, |p| p.y);
+            for p in &mut placed {
+                p.x = left;
+                p.y = y;
+                y += p.height;
+            }
+        }
+        Alignment::PackLeftToRight => {
+            placed.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let mut x = placed.first().map_or(0.0, |p| p.x);
+            for p in &mut placed {
+                p.x = x;
+                x += p.width;
+            }
+        }
+    }
+
+    for p in placed {
+        if let Some(pos) = positions.get_mut(p.idx) {
+            *pos = (p.x.round() as i32, p.y.round() as i32);
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, mode: (i32, i32), (x, y): (i32, i32)) -> Monitor {
+        Monitor {
+            x,
+            y,
+            ..Monitor::with_mode(name, mode)
+        }
+    }
+
+    #[test]
+    fn aligns_tops_to_the_topmost() {
+        let big = monitor("DP-1", (2560, 1440), (0, 200));
+        let small = monitor("DP-2", (1920, 1080), (2560, 100));
+        assert_eq!(
+            align(&[&big, &small], Alignment::Tops),
+            [(0, 100), (2560, 100)]
+        );
+    }
+
+    #[test]
+    fn aligns_bottoms_and_centres() {
+        let big = monitor("DP-1", (2560, 1440), (0, 0));
+        let small = monitor("DP-2", (1920, 1080), (2560, 0));
+        let monitors = [&big, &small];
+
+        assert_eq!(align(&monitors, Alignment::Bottoms), [(0, 0), (2560, 360)]);
+        assert_eq!(align(&monitors, Alignment::Centres), [(0, 0), (2560, 180)]);
+    }
+
+    #[test]
+    fn packs_and_distributes_in_order_of_x() {
+        let right = monitor("DP-3", (1920, 1080), (6000, 0));
+        let left = monitor("DP-1", (1920, 1080), (0, 0));
+        let middle = monitor("DP-2", (1920, 1080), (2200, 100));
+        let monitors = [&right, &left, &middle];
+
+        assert_eq!(
+            align(&monitors, Alignment::PackLeftToRight),
+            [(3840, 0), (0, 0), (1920, 100)],
+            "vertical positions are kept"
+        );
+        // 7920 wide with 5760 of outputs leaves two gaps of 1080
+        assert_eq!(
+            align(&monitors, Alignment::DistributeHorizontally),
+            [(6000, 0), (0, 0), (3000, 100)]
+        );
+    }
+
+    #[test]
+    fn stacks_below_the_topmost() {
+        let laptop = monitor("eDP-1", (1920, 1200), (500, 1440));
+        let external = monitor("DP-1", (2560, 1440), (0, 0));
+        assert_eq!(
+            align(&[&laptop, &external], Alignment::StackVertically),
+            [(0, 1440), (0, 0)]
+        );
+    }
+}
@@ -1,4 +1,80 @@
-use crate::models::Monitor;
+use crate::backend::Backend;
+use crate::canvas::CanvasView;
+use crate::diagnostics::Diagnostics;
+use crate::dpi;
+use crate::formats::{ExportFormat, Import, ImportFormat};
+use crate::history::History;
+use crate::models::{Monitor, OutputId, Profile};
+use crate::presets::Preset;
+use crate::worker::Worker;
+use std::collections::HashMap;
+
+/// Command line options
+#[derive(Default)]
+pub struct Options {
+    /// Backend requested with `--backend`, overriding detection and the settings menu
+    pub backend: Option<Backend>,
+    /// `--dry-run`: Apply only tests configurations
+    pub dry_run: bool,
+    /// `--switcher`: show the compact quick switcher instead of the full window
+    pub switcher: bool,
+}
+
+/// Something the quick switcher can apply
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SwitcherEntry {
+    Profile(usize),
+    Preset(Preset),
+}
+
+/// State of the quick switcher window
+#[derive(Default)]
+pub struct Switcher {
+    pub selected: usize,
+    /// Ctrl or Alt was held since the window opened, releasing it applies the selection
+    pub held: bool,
+    /// The selection was applied, the window closes once the backend is done
+    pub applied: bool,
+}
+
+/// State of the export window
+pub struct ExportDialog {
+    pub format: ExportFormat,
+    /// Name of the exported profile, for formats that have them
+    pub profile_name: String,
+    pub path: String,
+    /// Outcome of the last copy or save
+    pub status: Option<Result<String, String>>,
+}
+
+impl ExportDialog {
+    pub fn new(format: ExportFormat) -> Self {
+        Self {
+            format,
+            profile_name: "waydisplay".to_owned(),
+            path: format.default_path().display().to_string(),
+            status: None,
+        }
+    }
+}
+
+/// State of the import window
+pub struct ImportDialog {
+    pub format: ImportFormat,
+    pub path: String,
+    /// Outcome of the last import, or why reading the config failed
+    pub result: Option<Result<Import, String>>,
+}
+
+impl ImportDialog {
+    pub fn new(format: ImportFormat) -> Self {
+        Self {
+            format,
+            path: format.default_path().display().to_string(),
+            result: None,
+        }
+    }
+}
 
 /// Main application state
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -7,15 +83,69 @@ pub struct WayDisplay {
     pub monitors: Vec<Monitor>,
     pub selected_idx: Option<usize>,
     pub selected_mode_idx: Option<usize>,
-    pub adaptive_sync: bool,
-    pub monitor_enabled: bool,
     pub cmd_output: Option<String>,
+    /// Scale of the selected output as typed by the user
     pub scaling: String,
     pub monitor_count: i8,
     pub is_multi_monitor: bool,
+    /// Backend chosen in the settings menu, `None` means automatic detection
+    pub backend_override: Option<Backend>,
+    /// Poll the backend for output changes
+    pub live_refresh: bool,
+    pub profiles: Vec<Profile>,
+    /// Friendly names of outputs, e.g. "Left Dell"
+    pub aliases: HashMap<OutputId, String>,
+    /// Logical DPI scale suggestions aim for
+    pub target_dpi: f32,
+    /// Draw outputs in the alignment canvas at their size on the desk
+    pub physical_canvas: bool,
 
+    #[serde(skip)]
+    pub backend: Option<Backend>,
     #[serde(skip)]
     pub error_msg: Option<String>,
+    /// Shown instead of the display list while set
+    #[serde(skip)]
+    pub diagnostics: Option<Diagnostics>,
+    #[serde(skip)]
+    pub worker: Worker,
+    #[serde(skip)]
+    pub export: Option<ExportDialog>,
+    #[serde(skip)]
+    pub import: Option<ImportDialog>,
+    /// Output state last reported by the backend
+    #[serde(skip)]
+    pub live_monitors: Vec<Monitor>,
+    /// `egui` time of the last output change check
+    #[serde(skip)]
+    pub last_poll: f64,
+    #[serde(skip)]
+    pub history: History,
+    #[serde(skip)]
+    pub show_history: bool,
+    /// Label and outputs of the configuration being applied, recorded once it succeeds
+    #[serde(skip)]
+    pub applying: Option<(String, Vec<Monitor>)>,
+    /// Apply only tests configurations, set by `--dry-run`
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Outcome of the last configuration test
+    #[serde(skip)]
+    pub test_result: Option<Result<String, String>>,
+    /// Set when running as the `--switcher` window
+    #[serde(skip)]
+    pub switcher: Option<Switcher>,
+    /// Output being renamed and the alias typed so far
+    #[serde(skip)]
+    pub renaming: Option<(OutputId, String)>,
+    #[serde(skip)]
+    pub canvas: CanvasView,
+    /// Outputs shift-clicked in the canvas for the alignment tools
+    #[serde(skip)]
+    pub canvas_selection: Vec<usize>,
+    /// Backends found in `PATH`, looked up while the Backend menu is open
+    #[serde(skip)]
+    pub available_backends: Option<Vec<Backend>>,
 }
 
 impl Default for WayDisplay {
@@ -25,41 +155,91 @@ impl Default for WayDisplay {
             selected_idx: None,
             selected_mode_idx: None,
             error_msg: None,
-            adaptive_sync: false,
-            monitor_enabled: true,
             cmd_output: None,
             scaling: "1.0".to_owned(),
             monitor_count: 1,
             is_multi_monitor: true,
+            backend_override: None,
+            live_refresh: true,
+            profiles: Vec::new(),
+            aliases: HashMap::new(),
+            target_dpi: dpi::DEFAULT_TARGET_DPI,
+            physical_canvas: false,
+            backend: None,
+            diagnostics: None,
+            worker: Worker::default(),
+            export: None,
+            import: None,
+            live_monitors: Vec::new(),
+            last_poll: 0.0,
+            history: History::default(),
+            show_history: false,
+            applying: None,
+            dry_run: false,
+            test_result: None,
+            switcher: None,
+            renaming: None,
+            canvas: CanvasView::default(),
+            canvas_selection: Vec::new(),
+            available_backends: None,
         }
     }
 }
 
 impl WayDisplay {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(_cc: &eframe::CreationContext<'_>, options: &Options) -> Self {
         let mut app: Self = if let Some(storage) = _cc.storage {
             eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
         } else {
             Default::default()
         };
 
-        app.refresh_monitors();
+        app.dry_run = options.dry_run;
+        app.switcher = options.switcher.then(Switcher::default);
+        // A backend chosen in an earlier session may have been uninstalled since
+        let backend_override = app.backend_override.filter(|b| b.is_available());
+        app.detect_backend(options.backend.or(backend_override));
         app
     }
 }
 impl eframe::App for WayDisplay {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        // The switcher must not overwrite what the full window saved meanwhile
+        if self.switcher.is_none() {
+            eframe::set_value(storage, eframe::APP_KEY, self);
+        }
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_worker();
+        self.worker.start_queued();
+        self.watch_outputs(ctx);
         self.apply_style(ctx);
+
+        if self.switcher.is_some() {
+            self.render_switcher(ctx);
+            return;
+        }
+
+        self.handle_shortcuts(ctx);
         self.render_top_panel(ctx);
         self.render_bottom_panel(ctx);
+
+        if self.diagnostics.is_some() {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                self.render_diagnostics(ui);
+            });
+            return;
+        }
+
         self.render_side_panel(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             self.render_main_content(ui);
         });
+        self.render_export_window(ctx);
+        self.render_import_window(ctx);
+        self.render_history_window(ctx);
+        self.render_rename_window(ctx);
     }
 }
@@ -0,0 +1,162 @@
+use super::OutputConfig;
+use crate::models::{Mode, Monitor, Transform};
+use serde::Deserialize;
+use std::process::Command;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HyprMonitor {
+    name: String,
+    #[serde(default)]
+    make: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    serial: Option<String>,
+    width: i32,
+    height: i32,
+    refresh_rate: f32,
+    x: i32,
+    y: i32,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    /// `wl_output` transform number
+    #[serde(default)]
+    transform: usize,
+    #[serde(default)]
+    vrr: bool,
+    #[serde(default)]
+    disabled: bool,
+    /// Modes formatted as `1920x1080@60.00Hz`
+    #[serde(default)]
+    available_modes: Vec<String>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+pub fn list_command() -> Command {
+    let mut cmd = Command::new("hyprctl");
+    cmd.args(["monitors", "all", "-j"]);
+    cmd
+}
+
+fn parse_mode(mode: &str) -> Option<(i32, i32, f32)> {
+    let (size, refresh) = mode.split_once('@')?;
+    let (width, height) = size.split_once('x')?;
+    Some((
+        width.parse().ok()?,
+        height.parse().ok()?,
+        refresh.trim_end_matches("Hz").parse().ok()?,
+    ))
+}
+
+pub fn parse_monitors(json: &str) -> serde_json::Result<Vec<Monitor>> {
+    let monitors: Vec<HyprMonitor> = serde_json::from_str(json)?;
+    Ok(monitors
+        .into_iter()
+        .map(|m| {
+            let modes = m
+                .available_modes
+                .iter()
+                .filter_map(|s| parse_mode(s))
+                .map(|(width, height, refresh)| Mode {
+                    width,
+                    height,
+                    refresh,
+                    preferred: false,
+                    current: !m.disabled
+                        && width == m.width
+                        && height == m.height
+                        && (refresh - m.refresh_rate).abs() < 0.01,
+                })
+                .collect();
+            Monitor {
+                name: m.name,
+                make: m.make,
+                model: m.model,
+                serial: m.serial,
+                enabled: !m.disabled,
+                modes,
+                x: m.x,
+                y: m.y,
+                scale: m.scale,
+                transform: Transform::from_index(m.transform).unwrap_or_default(),
+                adaptive_sync: m.vrr,
+                physical_pos: egui::Pos2::ZERO,
+                edid: None,
+                edid_hash: None,
+                shared_id: false,
+                alias: None,
+                physical_size: None,
+            }
+        })
+        .collect())
+}
+
+pub fn apply_command(config: &OutputConfig<'_>) -> Command {
+    let mut cmd = Command::new("hyprctl");
+    cmd.args(["keyword", "monitor"]);
+
+    if config.enabled {
+        let mode = config.mode.map_or_else(
+            || "preferred".to_owned(),
+            |mode| format!("{}x{}@{}", mode.width, mode.height, mode.refresh),
+        );
+        let position = config
+            .position
+            .map_or_else(|| "auto".to_owned(), |(x, y)| format!("{x}x{y}"));
+        let mut rule = format!("{},{mode},{position},{}", config.name, config.scale);
+        rule.push_str(&format!(",transform,{}", config.transform.index()));
+        rule.push_str(if config.adaptive_sync {
+            ",vrr,1"
+        } else {
+            ",vrr,0"
+        });
+        cmd.arg(rule);
+    } else {
+        cmd.arg(format!("{},disable", config.name));
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HYPRLAND_MONITORS_JSON: &str =
+        include_str!("../../tests/fixtures/hyprland-monitors.json");
+
+    #[test]
+    fn parses_modes() {
+        assert_eq!(parse_mode("1920x1080@60.00Hz"), Some((1920, 1080, 60.0)));
+        assert_eq!(parse_mode("2560x1440@59.95"), Some((2560, 1440, 59.95)));
+        assert_eq!(parse_mode("1920x1080"), None);
+        assert_eq!(parse_mode("1920@60Hz"), None);
+        assert_eq!(parse_mode("widexhigh@60Hz"), None);
+    }
+
+    #[test]
+    fn parses_monitors() {
+        let monitors = parse_monitors(HYPRLAND_MONITORS_JSON).expect("fixture parses");
+        let [external, hdmi] = monitors.as_slice() else {
+            panic!("expected two monitors");
+        };
+
+        assert_eq!(external.description(), "Dell Inc. DELL U2720Q 8RZ1XC3");
+        assert!(external.enabled);
+        assert_eq!(external.scale, 1.5);
+        assert_eq!(external.transform, Transform::Rotate270);
+        assert!(external.adaptive_sync);
+        assert_eq!(external.modes.len(), 3, "unparsable modes are skipped");
+        assert_eq!(
+            external.current_mode_idx(),
+            Some(1),
+            "the current mode matches the refresh rate, not just the size"
+        );
+
+        assert!(!hdmi.enabled);
+        assert_eq!(hdmi.current_mode(), None, "disabled monitors have no mode");
+    }
+}
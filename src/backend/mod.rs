@@ -0,0 +1,215 @@
+mod hyprland;
+mod niri;
+mod sway;
+mod wlr;
+
+use crate::models::{Mode, Monitor, Transform};
+use crate::worker::{LIST_TIMEOUT, run_command};
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+
+/// Tool used to query and configure the outputs of the running compositor
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    WlrRandr,
+    Sway,
+    Hyprland,
+    Niri,
+}
+
+/// Settings to apply to a single output
+pub struct OutputConfig<'a> {
+    pub name: &'a str,
+    pub enabled: bool,
+    /// Mode to switch to, the current one is kept when unset
+    pub mode: Option<&'a Mode>,
+    pub position: Option<(i32, i32)>,
+    pub scale: f32,
+    pub transform: Transform,
+    pub adaptive_sync: bool,
+}
+
+impl Backend {
+    pub const ALL: [Self; 4] = [Self::WlrRandr, Self::Sway, Self::Hyprland, Self::Niri];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::WlrRandr => "wlr-randr",
+            Self::Sway => "Sway",
+            Self::Hyprland => "Hyprland",
+            Self::Niri => "niri",
+        }
+    }
+
+    /// Executable the backend talks to
+    pub fn program(self) -> &'static str {
+        match self {
+            Self::WlrRandr => "wlr-randr",
+            Self::Sway => "swaymsg",
+            Self::Hyprland => "hyprctl",
+            Self::Niri => "niri",
+        }
+    }
+
+    /// Parses the value of the `--backend` command line flag
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg.to_ascii_lowercase().as_str() {
+            "wlr-randr" | "wlr" | "wlroots" => Some(Self::WlrRandr),
+            "sway" | "swaymsg" => Some(Self::Sway),
+            "hyprland" | "hyprctl" => Some(Self::Hyprland),
+            "niri" => Some(Self::Niri),
+            _ => None,
+        }
+    }
+
+    pub fn is_available(self) -> bool {
+        find_in_path(self.program()).is_some()
+    }
+
+    /// Picks the best backend for the current session.
+    ///
+    /// Compositor specific IPC is preferred over wlr-randr, which is used for any
+    /// other compositor exposing `zwlr_output_manager_v1`. Returns `None` if
+    /// no backend can configure outputs in this session.
+    ///
+    /// Checking for the protocol runs wlr-randr, so this belongs on the
+    /// [`Worker`](crate::worker::Worker) thread.
+    pub fn detect(cancel: &AtomicBool) -> Option<Self> {
+        let desktop = env::var("XDG_CURRENT_DESKTOP")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let in_desktop = |name: &str| desktop.split(':').any(|d| d == name);
+
+        let candidates = [
+            (
+                env::var_os("SWAYSOCK").is_some() || in_desktop("sway"),
+                Self::Sway,
+            ),
+            (
+                env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() || in_desktop("hyprland"),
+                Self::Hyprland,
+            ),
+            (
+                env::var_os("NIRI_SOCKET").is_some() || in_desktop("niri"),
+                Self::Niri,
+            ),
+        ];
+        for (in_session, backend) in candidates {
+            if in_session && backend.is_available() {
+                log::info!("Detected {} session", backend.label());
+                return Some(backend);
+            }
+        }
+
+        if Self::WlrRandr.is_available() {
+            if wlr_output_management_available(cancel) {
+                return Some(Self::WlrRandr);
+            }
+            // Every apply would fail, let the diagnostics screen explain why instead
+            log::warn!("Compositor does not support zwlr_output_manager_v1");
+        }
+
+        None
+    }
+
+    /// Command printing the current output state as JSON
+    pub fn list_command(self) -> Command {
+        match self {
+            Self::WlrRandr => wlr::list_command(),
+            Self::Sway => sway::list_command(),
+            Self::Hyprland => hyprland::list_command(),
+            Self::Niri => niri::list_command(),
+        }
+    }
+
+    /// Parses the output of [`Self::list_command`]
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing why the JSON could not be parsed.
+    pub fn parse_monitors(self, json: &str) -> Result<Vec<Monitor>, String> {
+        let monitors = match self {
+            Self::WlrRandr => wlr::parse_monitors(json),
+            Self::Sway => sway::parse_monitors(json),
+            Self::Hyprland => hyprland::parse_monitors(json),
+            Self::Niri => niri::parse_monitors(json),
+        };
+        monitors.map_err(|e| format!("JSON Parse Error: {e}"))
+    }
+
+    /// Commands that apply `config`, to be run in order
+    pub fn apply_commands(self, config: &OutputConfig<'_>) -> Vec<Command> {
+        match self {
+            Self::WlrRandr => vec![wlr::apply_command(config)],
+            Self::Sway => vec![sway::apply_command(config)],
+            Self::Hyprland => vec![hyprland::apply_command(config)],
+            Self::Niri => niri::apply_commands(config),
+        }
+    }
+
+    /// Command that tests `configs` without applying them, `None` if the
+    /// backend has no way to do that
+    pub fn test_command(self, configs: &[OutputConfig<'_>]) -> Option<Command> {
+        match self {
+            Self::WlrRandr => Some(wlr::test_command(configs)),
+            Self::Sway | Self::Hyprland | Self::Niri => None,
+        }
+    }
+}
+
+/// Formats a command the way it would be typed into a shell
+pub fn command_line(cmd: &Command) -> String {
+    format!(
+        "{} {}",
+        cmd.get_program().to_string_lossy(),
+        cmd.get_args()
+            .map(|a| a.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ")
+    )
+}
+
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+}
+
+/// wlr-randr fails to start when the compositor lacks the output management protocol
+pub fn wlr_output_management_available(cancel: &AtomicBool) -> bool {
+    run_command(Command::new("wlr-randr"), LIST_TIMEOUT, cancel).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_backend_names() {
+        let args = [
+            ("wlr-randr", Some(Backend::WlrRandr)),
+            ("wlroots", Some(Backend::WlrRandr)),
+            ("Sway", Some(Backend::Sway)),
+            ("swaymsg", Some(Backend::Sway)),
+            ("HYPRLAND", Some(Backend::Hyprland)),
+            ("hyprctl", Some(Backend::Hyprland)),
+            ("niri", Some(Backend::Niri)),
+            ("kwin", None),
+            ("", None),
+        ];
+        for (arg, backend) in args {
+            assert_eq!(Backend::from_arg(arg), backend, "{arg:?}");
+        }
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = Backend::Sway
+            .parse_monitors("{}")
+            .expect_err("sway lists outputs in an array");
+        assert!(error.starts_with("JSON Parse Error: "), "{error}");
+    }
+}
@@ -0,0 +1,208 @@
+use super::OutputConfig;
+use crate::models::{Mode, Monitor, Transform};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+#[derive(Deserialize)]
+struct NiriMode {
+    width: i32,
+    height: i32,
+    /// Refresh rate in mHz
+    refresh_rate: i32,
+    #[serde(default)]
+    is_preferred: bool,
+}
+
+#[derive(Deserialize)]
+struct NiriLogical {
+    x: i32,
+    y: i32,
+    scale: f32,
+    /// `Normal`, `_90`, `Flipped270`, …
+    transform: String,
+}
+
+#[derive(Deserialize)]
+struct NiriOutput {
+    name: String,
+    #[serde(default)]
+    make: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    serial: Option<String>,
+    #[serde(default)]
+    modes: Vec<NiriMode>,
+    current_mode: Option<usize>,
+    logical: Option<NiriLogical>,
+    #[serde(default)]
+    vrr_enabled: bool,
+    /// Millimetres
+    physical_size: Option<(u32, u32)>,
+}
+
+fn parse_transform(name: &str) -> Transform {
+    match name {
+        "_90" => Transform::Rotate90,
+        "_180" => Transform::Rotate180,
+        "_270" => Transform::Rotate270,
+        "Flipped" => Transform::Flipped,
+        "Flipped90" => Transform::Flipped90,
+        "Flipped180" => Transform::Flipped180,
+        "Flipped270" => Transform::Flipped270,
+        _ => Transform::Normal,
+    }
+}
+
+pub fn list_command() -> Command {
+    let mut cmd = Command::new("niri");
+    cmd.args(["msg", "--json", "outputs"]);
+    cmd
+}
+
+pub fn parse_monitors(json: &str) -> serde_json::Result<Vec<Monitor>> {
+    let outputs: BTreeMap<String, NiriOutput> = serde_json::from_str(json)?;
+    Ok(outputs
+        .into_values()
+        .map(|o| {
+            let modes = o
+                .modes
+                .iter()
+                .enumerate()
+                .map(|(i, m)| Mode {
+                    width: m.width,
+                    height: m.height,
+                    refresh: m.refresh_rate as f32 / 1000.0,
+                    preferred: m.is_preferred,
+                    current: o.current_mode == Some(i),
+                })
+                .collect();
+            let (x, y) = o.logical.as_ref().map_or((0, 0), |l| (l.x, l.y));
+            let (scale, transform) = o.logical.as_ref().map_or((1.0, Transform::Normal), |l| {
+                (l.scale, parse_transform(&l.transform))
+            });
+            Monitor {
+                name: o.name,
+                make: o.make,
+                model: o.model,
+                serial: o.serial,
+                enabled: o.logical.is_some(),
+                modes,
+                x,
+                y,
+                scale,
+                transform,
+                adaptive_sync: o.vrr_enabled,
+                physical_pos: egui::Pos2::ZERO,
+                edid: None,
+                edid_hash: None,
+                shared_id: false,
+                alias: None,
+                physical_size: o.physical_size,
+            }
+        })
+        .collect())
+}
+
+fn output_command(name: &str) -> Command {
+    let mut cmd = Command::new("niri");
+    cmd.args(["msg", "output", name]);
+    cmd
+}
+
+/// niri only changes one property per `niri msg output` invocation
+pub fn apply_commands(config: &OutputConfig<'_>) -> Vec<Command> {
+    let name = config.name;
+    if !config.enabled {
+        let mut off = output_command(name);
+        off.arg("off");
+        return vec![off];
+    }
+
+    let mut on = output_command(name);
+    on.arg("on");
+    let mut commands = vec![on];
+
+    if let Some(mode) = config.mode {
+        let mut set_mode = output_command(name);
+        set_mode.arg("mode").arg(format!(
+            "{}x{}@{:.3}",
+            mode.width, mode.height, mode.refresh
+        ));
+        commands.push(set_mode);
+    }
+
+    let mut scale = output_command(name);
+    scale.arg("scale").arg(config.scale.to_string());
+
+    let mut transform = output_command(name);
+    transform.arg("transform").arg(config.transform.name());
+
+    let mut vrr = output_command(name);
+    vrr.arg("vrr")
+        .arg(if config.adaptive_sync { "on" } else { "off" });
+
+    commands.extend([scale, transform, vrr]);
+    if let Some((x, y)) = config.position {
+        let mut position = output_command(name);
+        position
+            .args(["position", "set"])
+            .arg(x.to_string())
+            .arg(y.to_string());
+        commands.push(position);
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NIRI_OUTPUTS_JSON: &str = include_str!("../../tests/fixtures/niri-outputs.json");
+
+    #[test]
+    fn parses_transform_names() {
+        let names = [
+            "Normal",
+            "_90",
+            "_180",
+            "_270",
+            "Flipped",
+            "Flipped90",
+            "Flipped180",
+            "Flipped270",
+        ];
+        assert_eq!(names.map(parse_transform), Transform::ALL);
+        assert_eq!(parse_transform("Sideways"), Transform::Normal);
+    }
+
+    #[test]
+    fn parses_outputs() {
+        let monitors = parse_monitors(NIRI_OUTPUTS_JSON).expect("fixture parses");
+        let names: Vec<_> = monitors.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["DP-1", "HDMI-A-1", "eDP-1"], "sorted by name");
+        let [external, tv, laptop] = monitors.as_slice() else {
+            panic!("expected three outputs");
+        };
+
+        assert_eq!(external.transform, Transform::Rotate90);
+        assert_eq!(external.scale, 1.5);
+        assert_eq!(external.physical_size, Some((600, 340)));
+
+        assert!(!tv.enabled, "outputs without a logical size are off");
+        assert_eq!(tv.current_mode(), None);
+        assert_eq!(tv.scale, 1.0);
+
+        assert!(laptop.enabled);
+        assert_eq!((laptop.x, laptop.y), (2560, 0));
+        assert_eq!(laptop.scale, 2.0);
+        assert_eq!(laptop.transform, Transform::Flipped90);
+        assert!(laptop.adaptive_sync);
+        let refresh: Vec<_> = laptop.modes.iter().map(|m| m.refresh).collect();
+        assert_eq!(refresh, [60.001, 120.0], "mHz become Hz");
+        assert_eq!(laptop.current_mode_idx(), Some(1));
+        let preferred: Vec<_> = laptop.modes.iter().map(|m| m.preferred).collect();
+        assert_eq!(preferred, [true, false]);
+    }
+}
@@ -0,0 +1,146 @@
+use super::OutputConfig;
+use crate::models::{Mode, Monitor, Transform};
+use serde::Deserialize;
+use std::process::Command;
+
+#[derive(Deserialize)]
+struct SwayMode {
+    width: i32,
+    height: i32,
+    /// Refresh rate in mHz
+    refresh: i32,
+}
+
+#[derive(Deserialize)]
+struct SwayRect {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize)]
+struct SwayOutput {
+    name: String,
+    #[serde(default)]
+    make: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    serial: Option<String>,
+    active: bool,
+    #[serde(default)]
+    modes: Vec<SwayMode>,
+    current_mode: Option<SwayMode>,
+    rect: SwayRect,
+    scale: Option<f32>,
+    transform: Option<String>,
+    /// `enabled` or `disabled`
+    adaptive_sync_status: Option<String>,
+}
+
+pub fn list_command() -> Command {
+    let mut cmd = Command::new("swaymsg");
+    cmd.args(["-t", "get_outputs", "--raw"]);
+    cmd
+}
+
+pub fn parse_monitors(json: &str) -> serde_json::Result<Vec<Monitor>> {
+    let outputs: Vec<SwayOutput> = serde_json::from_str(json)?;
+    Ok(outputs
+        .into_iter()
+        .map(|o| {
+            let current = o.current_mode.as_ref();
+            let modes = o
+                .modes
+                .iter()
+                .map(|m| Mode {
+                    width: m.width,
+                    height: m.height,
+                    refresh: m.refresh as f32 / 1000.0,
+                    preferred: false,
+                    current: o.active
+                        && current.is_some_and(|c| {
+                            c.width == m.width && c.height == m.height && c.refresh == m.refresh
+                        }),
+                })
+                .collect();
+            Monitor {
+                name: o.name,
+                make: o.make,
+                model: o.model,
+                serial: o.serial,
+                enabled: o.active,
+                modes,
+                x: o.rect.x,
+                y: o.rect.y,
+                scale: o.scale.filter(|s| *s > 0.0).unwrap_or(1.0),
+                transform: o
+                    .transform
+                    .as_deref()
+                    .and_then(Transform::from_name)
+                    .unwrap_or_default(),
+                adaptive_sync: o.adaptive_sync_status.as_deref() == Some("enabled"),
+                physical_pos: egui::Pos2::ZERO,
+                edid: None,
+                edid_hash: None,
+                shared_id: false,
+                alias: None,
+                physical_size: None,
+            }
+        })
+        .collect())
+}
+
+pub fn apply_command(config: &OutputConfig<'_>) -> Command {
+    let mut cmd = Command::new("swaymsg");
+    cmd.arg("output").arg(config.name);
+
+    if config.enabled {
+        cmd.arg("enable");
+        if let Some(mode) = config.mode {
+            cmd.arg("mode")
+                .arg(format!("{}x{}@{}Hz", mode.width, mode.height, mode.refresh));
+        }
+
+        if let Some((x, y)) = config.position {
+            cmd.arg("pos").arg(x.to_string()).arg(y.to_string());
+        }
+
+        cmd.arg("scale").arg(config.scale.to_string());
+        cmd.arg("transform").arg(config.transform.name());
+        cmd.arg("adaptive_sync")
+            .arg(if config.adaptive_sync { "on" } else { "off" });
+    } else {
+        cmd.arg("disable");
+    }
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWAY_OUTPUTS_JSON: &str = include_str!("../../tests/fixtures/sway-outputs.json");
+
+    #[test]
+    fn parses_outputs() {
+        let monitors = parse_monitors(SWAY_OUTPUTS_JSON).expect("fixture parses");
+        let [laptop, tv] = monitors.as_slice() else {
+            panic!("expected two outputs");
+        };
+
+        assert_eq!(laptop.name, "eDP-1");
+        assert!(laptop.enabled);
+        assert_eq!((laptop.x, laptop.y), (1440, 360));
+        assert_eq!(laptop.scale, 1.5);
+        assert_eq!(laptop.transform, Transform::Flipped90);
+        assert!(laptop.adaptive_sync);
+        let refresh: Vec<_> = laptop.modes.iter().map(|m| m.refresh).collect();
+        assert_eq!(refresh, [60.001, 120.0], "mHz become Hz");
+        assert_eq!(laptop.current_mode_idx(), Some(1));
+
+        assert!(!tv.enabled);
+        assert_eq!(tv.scale, 1.0, "disabled outputs report scale -1");
+        assert_eq!(tv.current_mode(), None);
+        assert!(!tv.adaptive_sync);
+    }
+}
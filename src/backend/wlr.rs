@@ -0,0 +1,145 @@
+use super::OutputConfig;
+use crate::models::{Mode, Monitor, Transform};
+use serde::Deserialize;
+use std::process::Command;
+
+#[derive(Deserialize)]
+struct WlrPosition {
+    x: i32,
+    y: i32,
+}
+
+/// Millimetres
+#[derive(Deserialize)]
+struct WlrSize {
+    width: u32,
+    height: u32,
+}
+
+#[derive(Deserialize)]
+struct WlrOutput {
+    name: String,
+    make: Option<String>,
+    model: Option<String>,
+    serial: Option<String>,
+    enabled: bool,
+    modes: Vec<Mode>,
+    position: Option<WlrPosition>,
+    #[serde(default)]
+    transform: Transform,
+    scale: Option<f32>,
+    #[serde(default)]
+    adaptive_sync: bool,
+    physical_size: Option<WlrSize>,
+}
+
+pub fn list_command() -> Command {
+    let mut cmd = Command::new("wlr-randr");
+    cmd.arg("--json");
+    cmd
+}
+
+pub fn parse_monitors(json: &str) -> serde_json::Result<Vec<Monitor>> {
+    let outputs: Vec<WlrOutput> = serde_json::from_str(json)?;
+    Ok(outputs
+        .into_iter()
+        .map(|o| {
+            let (x, y) = o.position.map_or((0, 0), |p| (p.x, p.y));
+            Monitor {
+                name: o.name,
+                make: o.make.unwrap_or_default(),
+                model: o.model.unwrap_or_default(),
+                serial: o.serial,
+                enabled: o.enabled,
+                modes: o.modes,
+                x,
+                y,
+                scale: o.scale.unwrap_or(1.0),
+                transform: o.transform,
+                adaptive_sync: o.adaptive_sync,
+                physical_pos: egui::Pos2::ZERO,
+                edid: None,
+                edid_hash: None,
+                shared_id: false,
+                alias: None,
+                physical_size: o.physical_size.map(|s| (s.width, s.height)),
+            }
+        })
+        .collect())
+}
+
+pub fn apply_command(config: &OutputConfig<'_>) -> Command {
+    let mut cmd = Command::new("wlr-randr");
+    output_args(&mut cmd, config);
+    cmd
+}
+
+/// Asks the compositor whether it would accept all of `configs` at once, without applying them
+pub fn test_command(configs: &[OutputConfig<'_>]) -> Command {
+    let mut cmd = Command::new("wlr-randr");
+    for config in configs {
+        output_args(&mut cmd, config);
+    }
+    cmd.arg("--dryrun");
+    cmd
+}
+
+fn output_args(cmd: &mut Command, config: &OutputConfig<'_>) {
+    cmd.arg("--output").arg(config.name);
+
+    if config.enabled {
+        cmd.arg("--on");
+        if let Some(mode) = config.mode {
+            cmd.arg("--custom-mode")
+                .arg(format!("{}x{}@{}", mode.width, mode.height, mode.refresh));
+        }
+
+        if let Some((x, y)) = config.position {
+            cmd.arg("--pos").arg(format!("{x},{y}"));
+        }
+
+        cmd.arg("--scale").arg(config.scale.to_string());
+        cmd.arg("--transform").arg(config.transform.name());
+        cmd.arg("--adaptive-sync").arg(if config.adaptive_sync {
+            "enabled"
+        } else {
+            "disabled"
+        });
+    } else {
+        cmd.arg("--off");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WLR_RANDR_JSON: &str = include_str!("../../tests/fixtures/wlr-randr.json");
+
+    #[test]
+    fn parses_outputs() {
+        let monitors = parse_monitors(WLR_RANDR_JSON).expect("fixture parses");
+        let [external, hdmi] = monitors.as_slice() else {
+            panic!("expected two outputs");
+        };
+
+        assert_eq!(external.name, "DP-1");
+        assert_eq!(external.description(), "Dell Inc. DELL U2720Q 8RZ1XC3");
+        assert!(external.enabled);
+        assert_eq!(external.scale, 1.5);
+        assert_eq!(external.transform, Transform::Rotate90);
+        assert!(external.adaptive_sync);
+        assert_eq!(external.physical_size, Some((600, 340)));
+        let current = external.current_mode().expect("DP-1 has a current mode");
+        assert_eq!((current.width, current.height), (3840, 2160));
+        assert!(current.preferred);
+
+        assert!(!hdmi.enabled);
+        assert_eq!(hdmi.make, "", "null make is empty");
+        assert_eq!(hdmi.serial, None);
+        assert_eq!((hdmi.x, hdmi.y), (0, 0), "missing position is the origin");
+        assert_eq!(hdmi.scale, 1.0, "missing scale is 1");
+        assert_eq!(hdmi.transform, Transform::Normal);
+        assert!(!hdmi.adaptive_sync);
+    }
+}
@@ -0,0 +1,538 @@
+use crate::align::Alignment;
+use crate::app::WayDisplay;
+use crate::models::Monitor;
+use crate::physical;
+use egui::{Align2, FontId, Pos2, Rect, RichText, Sense, Stroke, StrokeKind, Ui, Vec2, pos2, vec2};
+
+/// Height of the layout canvas
+const CANVAS_HEIGHT: f32 = 280.0;
+
+/// Space kept around the layout when fitting it into the canvas
+const FIT_MARGIN: f32 = 16.0;
+
+/// Distance in points within which a dragged output snaps to the edges of others
+const SNAP_DISTANCE: f32 = 10.0;
+
+/// Grid lines are drawn at least this many points apart
+const GRID_SPACING: f32 = 40.0;
+
+const LINE_HEIGHT: f32 = 13.0;
+
+/// Height of the tray of disabled outputs below the layout
+const TRAY_HEIGHT: f32 = 64.0;
+
+/// Zoom and pan of the layout canvas
+#[derive(Debug, Clone, Copy)]
+pub struct CanvasView {
+    /// Layout point drawn at the canvas' top-left corner
+    origin: Pos2,
+    /// Points per layout unit, a logical pixel or in the physical canvas a millimetre
+    zoom: f32,
+    /// Keep the whole layout in view as it changes
+    fit: bool,
+    /// Whether the view was last used for the physical canvas
+    physical: bool,
+}
+
+impl Default for CanvasView {
+    fn default() -> Self {
+        Self {
+            origin: Pos2::ZERO,
+            zoom: 0.05,
+            fit: true,
+            physical: false,
+        }
+    }
+}
+
+impl CanvasView {
+    fn screen_rect(self, canvas: Rect, rect: Rect) -> Rect {
+        Rect::from_min_size(
+            canvas.min + (rect.min - self.origin) * self.zoom,
+            rect.size() * self.zoom,
+        )
+    }
+
+    fn layout_pos(self, canvas: Rect, pos: Pos2) -> Pos2 {
+        self.origin + (pos - canvas.min) / self.zoom
+    }
+
+    /// Zooms by `factor`, keeping the layout point under `pos` in place
+    fn zoom_at(&mut self, canvas: Rect, pos: Pos2, factor: f32) {
+        let anchor = self.layout_pos(canvas, pos);
+        self.zoom = (self.zoom * factor).clamp(0.002, 20.0);
+        self.origin = anchor - (pos - canvas.min) / self.zoom;
+        self.fit = false;
+    }
+
+    /// Zooms and pans so `bounds` fills the canvas
+    fn fit_to(&mut self, canvas: Rect, bounds: Rect) {
+        let room = (canvas.size() - Vec2::splat(2.0 * FIT_MARGIN)).max(Vec2::splat(1.0));
+        let size = bounds.size().max(Vec2::splat(1.0));
+        self.zoom = (room.x / size.x).min(room.y / size.y);
+        self.origin = bounds.center() - canvas.size() / 2.0 / self.zoom;
+    }
+}
+
+impl WayDisplay {
+    /// Draws the layout canvas where outputs are arranged by dragging.
+    ///
+    /// Returns the label of the edit made, if any.
+    pub fn render_canvas(&mut self, ui: &mut Ui) -> Option<String> {
+        let physical_canvas = self.physical_canvas;
+        if self.canvas.physical != physical_canvas {
+            self.canvas.physical = physical_canvas;
+            self.canvas.fit = true;
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .small_button(egui_phosphor::regular::CORNERS_OUT)
+                .on_hover_text("Fit the layout (double-click the background)")
+                .clicked()
+            {
+                self.canvas.fit = true;
+            }
+            ui.label(RichText::new("Scroll to zoom, drag the background to pan").weak());
+        });
+        let mut alignment = None;
+        ui.horizontal_wrapped(|ui| {
+            let several = self.canvas_selection.len() > 1;
+            for a in Alignment::ALL {
+                if ui
+                    .add_enabled(several, egui::Button::new(a.label()).small())
+                    .on_disabled_hover_text("Shift-click outputs to select several")
+                    .clicked()
+                {
+                    alignment = Some(a);
+                }
+            }
+        });
+        if let Some(alignment) = alignment {
+            self.align_selection(alignment);
+        }
+
+        let (canvas, background) = ui.allocate_exact_size(
+            vec2(ui.available_width(), CANVAS_HEIGHT),
+            Sense::click_and_drag(),
+        );
+        ui.painter_at(canvas)
+            .rect_filled(canvas, 3.0, ui.visuals().extreme_bg_color);
+
+        // Disabled outputs wait in a tray along the bottom, outside the layout
+        let disabled: Vec<usize> = (0..self.monitors.len())
+            .filter(|&i| self.monitors.get(i).is_some_and(|m| !m.enabled))
+            .collect();
+        let area = if disabled.is_empty() {
+            canvas
+        } else {
+            Rect::from_min_max(canvas.min, pos2(canvas.max.x, canvas.max.y - TRAY_HEIGHT))
+        };
+        let tray = Rect::from_min_max(pos2(canvas.min.x, area.max.y), canvas.max);
+
+        let boxes: Vec<Option<Rect>> = self
+            .monitors
+            .iter()
+            .map(|m| m.enabled.then(|| layout_box(m, physical_canvas)))
+            .collect();
+
+        // Refitting while an output is dragged would move the view under the pointer
+        if self.canvas.fit && !background.dragged() && ui.ctx().dragged_id().is_none() {
+            let bounds = boxes
+                .iter()
+                .flatten()
+                .fold(Rect::NOTHING, |b, r| b.union(*r));
+            if bounds.is_positive() {
+                self.canvas.fit_to(area, bounds);
+            }
+        }
+
+        if let Some(pointer) = ui.ctx().pointer_hover_pos().filter(|p| area.contains(*p)) {
+            let (scroll, pinch) = ui.input_mut(|i| {
+                let scroll = i.smooth_scroll_delta.y;
+                i.smooth_scroll_delta = Vec2::ZERO;
+                (scroll, i.zoom_delta())
+            });
+            let factor = pinch * (scroll / 200.0).exp();
+            if factor != 1.0 {
+                self.canvas.zoom_at(area, pointer, factor);
+            }
+        }
+        if background.dragged() {
+            self.canvas.origin -= background.drag_delta() / self.canvas.zoom;
+            self.canvas.fit = false;
+        }
+        if background.double_clicked() {
+            self.canvas.fit = true;
+        }
+
+        self.draw_grid(ui, area);
+
+        let view = self.canvas;
+        let painter = ui.painter_at(area);
+        let mut edit = None;
+        let mut clicked = None;
+        for (i, &local) in boxes.iter().enumerate() {
+            let Some(local) = local else {
+                continue;
+            };
+            let id = ui.make_persistent_id(("canvas_output", i));
+            let res = ui.interact(
+                view.screen_rect(area, local).intersect(area),
+                id,
+                Sense::click_and_drag(),
+            );
+            if res.clicked() {
+                clicked = Some((i, ui.input(|input| input.modifiers.shift)));
+            }
+
+            let mut moved = local;
+            if res.dragged() {
+                // Follow the pointer from where the drag started, so snapping
+                // doesn't hold on to the output
+                let start = if res.drag_started() {
+                    local.min
+                } else {
+                    ui.data(|d| d.get_temp(id)).unwrap_or(local.min)
+                };
+                let raw = start + res.drag_delta() / view.zoom;
+                ui.data_mut(|d| d.insert_temp(id, raw));
+
+                moved = Rect::from_min_size(raw, local.size());
+                let others: Vec<Rect> = boxes
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .filter_map(|(_, r)| *r)
+                    .collect();
+                moved = moved.translate(snap(moved, &others, SNAP_DISTANCE / view.zoom));
+            }
+
+            if moved != local {
+                self.move_in_canvas(i, moved.min);
+                if let Some(m) = self.monitors.get(i) {
+                    edit = Some(format!("Move {}", m.name));
+                }
+            }
+            if res.drag_stopped() {
+                self.settle_layout(i);
+            }
+
+            if let Some(m) = self.monitors.get(i) {
+                let rect = view.screen_rect(area, moved);
+                let selected = if self.canvas_selection.is_empty() {
+                    self.selected_idx == Some(i)
+                } else {
+                    self.canvas_selection.contains(&i)
+                };
+                draw_output(ui, &painter, m, rect, selected);
+                res.on_hover_ui(|ui| output_details(ui, m));
+            }
+        }
+
+        if !disabled.is_empty() {
+            let layout: Vec<Rect> = boxes.iter().flatten().copied().collect();
+            if let Some(label) = self.render_tray(ui, canvas, area, tray, &disabled, &layout) {
+                edit = Some(label);
+            }
+        }
+
+        match clicked {
+            Some((i, true)) => self.toggle_canvas_selection(i),
+            Some((i, false)) => {
+                self.canvas_selection.clear();
+                self.select_monitor(i);
+            }
+            None => {}
+        }
+        edit
+    }
+
+    /// Draws the disabled outputs in `tray`, enabling those dragged into the
+    /// layout `area`. `layout` are the boxes of the enabled outputs.
+    fn render_tray(
+        &mut self,
+        ui: &Ui,
+        canvas: Rect,
+        area: Rect,
+        tray: Rect,
+        disabled: &[usize],
+        layout: &[Rect],
+    ) -> Option<String> {
+        let visuals = ui.visuals();
+        let painter = ui.painter_at(canvas);
+        painter.hline(
+            tray.x_range(),
+            tray.min.y,
+            visuals.widgets.noninteractive.bg_stroke,
+        );
+        painter.text(
+            tray.left_top() + vec2(6.0, 4.0),
+            Align2::LEFT_TOP,
+            "Disabled, drag into the layout to enable",
+            FontId::proportional(10.0),
+            visuals.weak_text_color(),
+        );
+
+        let view = self.canvas;
+        // Each output at the size enabling it gives, since the preferred mode
+        // is picked then
+        let outputs: Vec<(usize, Vec2)> = disabled
+            .iter()
+            .filter_map(|&i| {
+                let mut m = self.monitors.get(i)?.clone();
+                m.ensure_mode();
+                Some((i, layout_box(&m, self.physical_canvas).size()))
+            })
+            .collect();
+        // Shrink the slots when they don't fit side by side
+        let height = TRAY_HEIGHT - 24.0;
+        let slot_width = |size: Vec2| (height * size.x / size.y).clamp(height * 0.5, height * 2.5);
+        let widths: f32 = outputs
+            .iter()
+            .map(|&(_, size)| slot_width(size) + 6.0)
+            .sum();
+        let shrink = ((tray.width() - 6.0) / widths).min(1.0);
+
+        let mut x = tray.min.x + 6.0;
+        let mut edit = None;
+        for (i, size) in outputs {
+            let Some(m) = self.monitors.get(i) else {
+                continue;
+            };
+            let slot_size = vec2(slot_width(size), height) * shrink;
+            let slot = Rect::from_min_size(pos2(x, tray.max.y - slot_size.y - 4.0), slot_size);
+            x += slot_size.x + 6.0 * shrink;
+
+            let id = ui.make_persistent_id(("canvas_disabled", i));
+            let res = ui.interact(slot, id, Sense::click_and_drag());
+            let pointer = ui.ctx().pointer_latest_pos();
+            let rect = match pointer.filter(|_| res.dragged()) {
+                // Shown at the size it will have in the layout
+                Some(pointer) => Rect::from_center_size(pointer, size * view.zoom),
+                None => slot,
+            };
+            let selected = self.selected_idx == Some(i);
+            draw_disabled(ui, &painter, m, rect, selected);
+
+            let name = m.name.clone();
+            let res = res.on_hover_ui(|ui| output_details(ui, m));
+            if res.clicked() {
+                self.canvas_selection.clear();
+                self.select_monitor(i);
+            }
+            if res.drag_stopped()
+                && let Some(pointer) = pointer.filter(|p| area.contains(*p))
+            {
+                self.enable_output(i);
+                let Some(size) = self
+                    .monitors
+                    .get(i)
+                    .map(|m| layout_box(m, self.physical_canvas).size())
+                else {
+                    continue;
+                };
+                let dropped = Rect::from_center_size(view.layout_pos(area, pointer), size);
+                let dropped = dropped.translate(snap(dropped, layout, SNAP_DISTANCE / view.zoom));
+                self.move_in_canvas(i, dropped.min);
+                self.settle_layout(i);
+                edit = Some(format!("Enable {name}"));
+            }
+        }
+        edit
+    }
+
+    /// Moves output `idx` to `pos` in the canvas' units
+    fn move_in_canvas(&mut self, idx: usize, pos: Pos2) {
+        if self.physical_canvas {
+            if let Some(m) = self.monitors.get_mut(idx) {
+                m.physical_pos = pos;
+            }
+        } else {
+            self.move_output(idx, pos.x.round() as i32, pos.y.round() as i32);
+        }
+    }
+
+    /// Derives the final logical positions once output `idx` was dropped
+    fn settle_layout(&mut self, idx: usize) {
+        if self.physical_canvas {
+            self.apply_physical_positions();
+        } else if let Some(before) = self.monitors.get(idx).map(|m| (m.x, m.y)) {
+            // Keep the layout where it is on screen
+            self.normalize_positions();
+            if let Some(m) = self.monitors.get(idx) {
+                self.canvas.origin += vec2((m.x - before.0) as f32, (m.y - before.1) as f32);
+            }
+        }
+    }
+
+    fn draw_grid(&self, ui: &Ui, canvas: Rect) {
+        let view = self.canvas;
+        // Smallest 1, 2 or 5 times a power of ten that keeps lines apart
+        let min_step = GRID_SPACING / view.zoom;
+        let magnitude = 10f32.powf(min_step.log10().floor());
+        let step = [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|f| f * magnitude)
+            .find(|s| *s >= min_step)
+            .unwrap_or(10.0 * magnitude);
+
+        let painter = ui.painter_at(canvas);
+        let color = ui.visuals().widgets.noninteractive.bg_stroke.color;
+        let top_left = view.origin;
+        let bottom_right = view.layout_pos(canvas, canvas.max);
+
+        let mut x = (top_left.x / step).ceil() * step;
+        while x <= bottom_right.x {
+            let screen_x = canvas.min.x + (x - top_left.x) * view.zoom;
+            let width: f32 = if x == 0.0 { 1.5 } else { 0.5 };
+            painter.vline(screen_x, canvas.y_range(), Stroke::new(width, color));
+            x += step;
+        }
+        let mut y = (top_left.y / step).ceil() * step;
+        while y <= bottom_right.y {
+            let screen_y = canvas.min.y + (y - top_left.y) * view.zoom;
+            let width: f32 = if y == 0.0 { 1.5 } else { 0.5 };
+            painter.hline(canvas.x_range(), screen_y, Stroke::new(width, color));
+            y += step;
+        }
+    }
+}
+
+/// Where `monitor` is in the layout, in logical pixels or, in the physical
+/// canvas, in millimetres on the desk
+fn layout_box(monitor: &Monitor, physical_canvas: bool) -> Rect {
+    if physical_canvas {
+        let size = physical::size_mm(monitor).unwrap_or((480.0, 270.0));
+        Rect::from_min_size(monitor.physical_pos, vec2(size.0, size.1))
+    } else {
+        let size = monitor.logical_size().unwrap_or((1920.0, 1080.0));
+        Rect::from_min_size(
+            Pos2::new(monitor.x as f32, monitor.y as f32),
+            vec2(size.0, size.1),
+        )
+    }
+}
+
+/// Draws the box of `monitor` with as many of its name, resolution, scale
+/// and position as fit
+fn draw_output(ui: &Ui, painter: &egui::Painter, monitor: &Monitor, rect: Rect, selected: bool) {
+    let visuals = ui.visuals();
+    let fill = if selected {
+        visuals.selection.bg_fill
+    } else {
+        visuals.widgets.inactive.bg_fill
+    };
+    painter.rect_filled(rect, 2.0, fill);
+    painter.rect_stroke(
+        rect,
+        2.0,
+        visuals.widgets.active.fg_stroke,
+        StrokeKind::Middle,
+    );
+
+    let mut lines = vec![monitor.label().to_owned()];
+    if let Some(mode) = monitor.current_mode() {
+        lines.push(format!("{}x{}", mode.width, mode.height));
+    }
+    lines.push(format!("Scale {}", monitor.scale));
+    lines.push(format!("{}, {}", monitor.x, monitor.y));
+    lines.truncate(((rect.height() - 4.0) / LINE_HEIGHT).max(1.0) as usize);
+
+    let painter = painter.with_clip_rect(rect.intersect(painter.clip_rect()));
+    let top = rect.center().y - (lines.len() - 1) as f32 * LINE_HEIGHT / 2.0;
+    for (n, line) in lines.iter().enumerate() {
+        painter.text(
+            egui::pos2(rect.center().x, top + n as f32 * LINE_HEIGHT),
+            Align2::CENTER_CENTER,
+            line,
+            FontId::proportional(11.0),
+            visuals.text_color(),
+        );
+    }
+}
+
+/// Draws the greyed-out box of a disabled output with its name
+fn draw_disabled(ui: &Ui, painter: &egui::Painter, monitor: &Monitor, rect: Rect, selected: bool) {
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.widgets.noninteractive.bg_fill);
+    let stroke = if selected {
+        visuals.selection.stroke
+    } else {
+        visuals.widgets.noninteractive.bg_stroke
+    };
+    painter.rect_stroke(rect, 2.0, stroke, StrokeKind::Middle);
+    painter
+        .with_clip_rect(rect.intersect(painter.clip_rect()))
+        .text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            monitor.label(),
+            FontId::proportional(11.0),
+            visuals.weak_text_color(),
+        );
+}
+
+fn output_details(ui: &mut Ui, monitor: &Monitor) {
+    ui.label(RichText::new(monitor.label()).strong());
+    egui::Grid::new("output_details")
+        .num_columns(2)
+        .show(ui, |ui| {
+            let mode = monitor.current_mode().map_or_else(
+                || "None".to_owned(),
+                |m| format!("{}x{} @ {:.2} Hz", m.width, m.height, m.refresh),
+            );
+            let logical = monitor
+                .logical_size()
+                .map_or_else(String::new, |(w, h)| format!("{w:.0}x{h:.0}"));
+            let physical = monitor
+                .physical_size_mm()
+                .map_or_else(String::new, |(w, h)| format!("{w:.0} x {h:.0} mm"));
+            for (label, value) in [
+                ("Connector", monitor.name.clone()),
+                ("Display", monitor.description()),
+                ("Mode", mode),
+                ("Scale", monitor.scale.to_string()),
+                ("Transform", monitor.transform.name().to_owned()),
+                ("Position", format!("{}, {}", monitor.x, monitor.y)),
+                ("Logical size", logical),
+                ("Physical size", physical),
+            ] {
+                if value.is_empty() {
+                    continue;
+                }
+                ui.label(label);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+/// Offset that moves `rect` onto the nearest edge of `others` within `distance`,
+/// horizontally and vertically
+fn snap(rect: Rect, others: &[Rect], distance: f32) -> Vec2 {
+    let nearest = |offsets: &mut dyn Iterator<Item = f32>| {
+        offsets
+            .filter(|d| d.abs() < distance)
+            .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(0.0)
+    };
+    let x = nearest(&mut others.iter().flat_map(|o| {
+        [
+            o.right() - rect.left(),
+            o.left() - rect.right(),
+            o.left() - rect.left(),
+            o.right() - rect.right(),
+        ]
+    }));
+    let y = nearest(&mut others.iter().flat_map(|o| {
+        [
+            o.bottom() - rect.top(),
+            o.top() - rect.bottom(),
+            o.top() - rect.top(),
+            o.bottom() - rect.bottom(),
+            o.center().y - rect.center().y,
+        ]
+    }));
+    vec2(x, y)
+}
@@ -0,0 +1,118 @@
+use crate::backend::{Backend, find_in_path, wlr_output_management_available};
+use std::env;
+use std::sync::atomic::AtomicBool;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeStatus {
+    Found,
+    Missing,
+    /// The probe could not be run, e.g. because a required tool is missing
+    Unknown,
+}
+
+/// Result of checking for a single tool, protocol or session variable
+pub struct Probe {
+    pub name: String,
+    pub status: ProbeStatus,
+    pub detail: String,
+    /// How to fix a missing probe, empty if there is nothing to install
+    pub hint: &'static str,
+}
+
+/// Everything probed while looking for a usable display backend
+pub struct Diagnostics {
+    pub tools: Vec<Probe>,
+    pub protocols: Vec<Probe>,
+    pub session: Vec<Probe>,
+}
+
+fn install_hint(backend: Backend) -> &'static str {
+    match backend {
+        Backend::WlrRandr => {
+            "Install the `wlr-randr` package (e.g. `pacman -S wlr-randr`, `apt install wlr-randr`, `dnf install wlr-randr`)."
+        }
+        Backend::Sway => "`swaymsg` ships with the `sway` package.",
+        Backend::Hyprland => "`hyprctl` ships with the `hyprland` package.",
+        Backend::Niri => "The `niri` binary ships with the `niri` package.",
+    }
+}
+
+impl Diagnostics {
+    /// Checks the tools, protocols and session, see [`Backend::detect`] for
+    /// why this runs on the worker thread
+    pub fn probe(cancel: &AtomicBool) -> Self {
+        let tools = Backend::ALL
+            .into_iter()
+            .map(|backend| {
+                let path = find_in_path(backend.program());
+                Probe {
+                    name: backend.program().to_owned(),
+                    status: if path.is_some() {
+                        ProbeStatus::Found
+                    } else {
+                        ProbeStatus::Missing
+                    },
+                    detail: path.map_or_else(
+                        || "not found in PATH".to_owned(),
+                        |p| p.display().to_string(),
+                    ),
+                    hint: install_hint(backend),
+                }
+            })
+            .collect();
+
+        let output_management = if Backend::WlrRandr.is_available() {
+            if wlr_output_management_available(cancel) {
+                Probe {
+                    name: "zwlr_output_manager_v1".to_owned(),
+                    status: ProbeStatus::Found,
+                    detail: "advertised by the compositor".to_owned(),
+                    hint: "",
+                }
+            } else {
+                Probe {
+                    name: "zwlr_output_manager_v1".to_owned(),
+                    status: ProbeStatus::Missing,
+                    detail: "wlr-randr could not bind the protocol".to_owned(),
+                    hint: "Use a wlroots based compositor, or the compositor specific backend.",
+                }
+            }
+        } else {
+            Probe {
+                name: "zwlr_output_manager_v1".to_owned(),
+                status: ProbeStatus::Unknown,
+                detail: "requires wlr-randr to check".to_owned(),
+                hint: install_hint(Backend::WlrRandr),
+            }
+        };
+
+        let session = [
+            "WAYLAND_DISPLAY",
+            "XDG_CURRENT_DESKTOP",
+            "SWAYSOCK",
+            "HYPRLAND_INSTANCE_SIGNATURE",
+            "NIRI_SOCKET",
+        ]
+        .into_iter()
+        .map(|var| {
+            let value = env::var(var).ok();
+            Probe {
+                name: var.to_owned(),
+                status: if value.is_some() {
+                    ProbeStatus::Found
+                } else {
+                    ProbeStatus::Missing
+                },
+                detail: value.unwrap_or_else(|| "not set".to_owned()),
+                hint: "",
+            }
+        })
+        .collect();
+
+        Self {
+            tools,
+            protocols: vec![output_management],
+            session,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::formats::format_refresh;
+use crate::models::{Mode, Monitor};
+
+/// A setting whose pending value differs from the live one
+pub struct Change {
+    pub setting: &'static str,
+    pub live: String,
+    pub pending: String,
+}
+
+/// What applying would change on one output
+pub struct OutputDiff {
+    pub name: String,
+    pub changes: Vec<Change>,
+}
+
+/// Compares every `pending` output with its `live` state, listing only outputs
+/// with changes. Outputs the backend doesn't report are skipped.
+pub fn diff(live: &[Monitor], pending: &[Monitor]) -> Vec<OutputDiff> {
+    pending
+        .iter()
+        .filter_map(|p| {
+            let l = live.iter().find(|l| l.is_same_output(p))?;
+            let changes = output_changes(l, p);
+            (!changes.is_empty()).then(|| OutputDiff {
+                name: p.name.clone(),
+                changes,
+            })
+        })
+        .collect()
+}
+
+fn output_changes(live: &Monitor, pending: &Monitor) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut push = |setting, live: String, pending: String| {
+        if live != pending {
+            changes.push(Change {
+                setting,
+                live,
+                pending,
+            });
+        }
+    };
+
+    push("Enabled", on_off(live.enabled), on_off(pending.enabled));
+    // Nothing else is applied to an output that stays off
+    if !pending.enabled {
+        return changes;
+    }
+
+    push(
+        "Mode",
+        mode_label(live.current_mode()),
+        mode_label(pending.current_mode()),
+    );
+    push(
+        "Position",
+        format!("{}, {}", live.x, live.y),
+        format!("{}, {}", pending.x, pending.y),
+    );
+    push("Scale", live.scale.to_string(), pending.scale.to_string());
+    push(
+        "VRR",
+        on_off(live.adaptive_sync),
+        on_off(pending.adaptive_sync),
+    );
+    push(
+        "Transform",
+        live.transform.name().to_owned(),
+        pending.transform.name().to_owned(),
+    );
+    changes
+}
+
+fn on_off(value: bool) -> String {
+    if value { "on" } else { "off" }.to_owned()
+}
+
+fn mode_label(mode: Option<&Mode>) -> String {
+    mode.map_or_else(
+        || "none".to_owned(),
+        |m| {
+            format!(
+                "{}x{} @ {} Hz",
+                m.width,
+                m.height,
+                format_refresh(m.refresh)
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Transform;
+
+    fn external(name: &str) -> Monitor {
+        Monitor {
+            make: "Dell Inc.".to_owned(),
+            model: "DELL U2720Q".to_owned(),
+            serial: Some("8RZ1XC3".to_owned()),
+            ..Monitor::with_mode(name, (3840, 2160))
+        }
+    }
+
+    fn changes(live: &Monitor, pending: &Monitor) -> Vec<(&'static str, String, String)> {
+        output_changes(live, pending)
+            .into_iter()
+            .map(|c| (c.setting, c.live, c.pending))
+            .collect()
+    }
+
+    fn change(setting: &'static str, live: &str, pending: &str) -> (&'static str, String, String) {
+        (setting, live.to_owned(), pending.to_owned())
+    }
+
+    #[test]
+    fn lists_every_changed_setting() {
+        let live = external("DP-1");
+        let mut pending = Monitor {
+            x: 1920,
+            y: -200,
+            scale: 1.5,
+            adaptive_sync: true,
+            transform: Transform::Rotate270,
+            ..live.clone()
+        };
+        pending.modes.push(Mode {
+            width: 2560,
+            height: 1440,
+            refresh: 59.951,
+            preferred: false,
+            current: false,
+        });
+        pending.set_current_mode(1);
+
+        assert_eq!(
+            changes(&live, &pending),
+            [
+                change("Mode", "3840x2160 @ 60 Hz", "2560x1440 @ 59.951 Hz"),
+                change("Position", "0, 0", "1920, -200"),
+                change("Scale", "1", "1.5"),
+                change("VRR", "off", "on"),
+                change("Transform", "normal", "270"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_only_the_switch_for_outputs_turned_off() {
+        let live = external("DP-1");
+        let off = Monitor {
+            enabled: false,
+            scale: 2.0,
+            ..live.clone()
+        };
+        assert_eq!(changes(&live, &off), [change("Enabled", "on", "off")]);
+
+        let on = Monitor {
+            x: 100,
+            ..live.clone()
+        };
+        assert_eq!(
+            changes(&off, &on),
+            [
+                change("Enabled", "off", "on"),
+                change("Position", "0, 0", "100, 0"),
+                change("Scale", "2", "1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_layouts_have_no_diff() {
+        let layout = [external("DP-1"), Monitor::with_mode("eDP-1", (2880, 1800))];
+        assert!(diff(&layout, &layout).is_empty());
+    }
+
+    #[test]
+    fn matches_outputs_by_identity() {
+        let laptop = Monitor {
+            x: 3840,
+            ..Monitor::with_mode("eDP-1", (2880, 1800))
+        };
+        let live = [external("DP-1"), laptop.clone()];
+        let moved = Monitor {
+            x: 2880,
+            ..external("DP-2")
+        };
+        let pending = [Monitor { x: 0, ..laptop }, moved];
+
+        let names: Vec<_> = diff(&live, &pending)
+            .into_iter()
+            .map(|d| (d.name, d.changes.len()))
+            .collect();
+        assert_eq!(
+            names,
+            [("eDP-1".to_owned(), 1), ("DP-2".to_owned(), 1)],
+            "the Dell is found on its new connector"
+        );
+
+        let other = Monitor {
+            serial: Some("5KTMXC3".to_owned()),
+            x: 100,
+            ..external("DP-1")
+        };
+        assert!(
+            diff(&live, &[other]).is_empty(),
+            "another Dell on DP-1 is not the live one"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::models::Monitor;
+
+/// Logical DPI desktops are designed for, what scale 1 gives on a 96 PPI screen
+pub const DEFAULT_TARGET_DPI: f32 = 96.0;
+
+/// Suggested scales are multiples of this, which fractional scaling represents exactly
+const SCALE_STEP: f32 = 0.125;
+/// Range of suggested scales, shrinking low-PPI panels below 1 only blurs them
+const MIN_SCALE: f32 = 1.0;
+const MAX_SCALE: f32 = 4.0;
+
+/// Pixels per inch of `monitor` in its current mode, `None` without a mode or
+/// physical size
+pub fn ppi(monitor: &Monitor) -> Option<f32> {
+    let mode = monitor.current_mode()?;
+    let (width_mm, height_mm) = monitor.physical_size_mm()?;
+    let diagonal_px = (mode.width as f32).hypot(mode.height as f32);
+    Some(diagonal_px / width_mm.hypot(height_mm) * 25.4)
+}
+
+/// Scale at which `ppi` gives `target_dpi` logical DPI
+pub fn suggest_scale(ppi: f32, target_dpi: f32) -> f32 {
+    round_scale(ppi / target_dpi).clamp(MIN_SCALE, MAX_SCALE)
+}
+
+/// Scales for every output so text appears the same physical size as on
+/// `reference`, `None` for outputs whose PPI is unknown
+pub fn matching_scales(monitors: &[Monitor], reference: &Monitor) -> Vec<Option<f32>> {
+    let Some(reference_dpi) = ppi(reference).map(|ppi| ppi / reference.scale) else {
+        return vec![None; monitors.len()];
+    };
+    monitors
+        .iter()
+        .map(|m| Some(round_scale(ppi(m)? / reference_dpi).clamp(MIN_SCALE, MAX_SCALE)))
+        .collect()
+}
+
+fn round_scale(scale: f32) -> f32 {
+    (scale / SCALE_STEP).round() * SCALE_STEP
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(mode: (i32, i32), scale: f32, mm: Option<(u32, u32)>) -> Monitor {
+        Monitor {
+            scale,
+            physical_size: mm,
+            ..Monitor::with_mode("DP-1", mode)
+        }
+    }
+
+    #[test]
+    fn ppi_from_mode_and_size() {
+        // 4 pixels per mm
+        let ppi = ppi(&monitor((1920, 1080), 1.0, Some((480, 270)))).unwrap_or_default();
+        assert!((ppi - 101.6).abs() < 0.01, "got {ppi}");
+
+        assert_eq!(super::ppi(&monitor((1920, 1080), 1.0, None)), None);
+    }
+
+    #[test]
+    fn scales_round_to_steps() {
+        assert_eq!(round_scale(1.3), 1.25);
+        assert_eq!(round_scale(1.45), 1.5);
+        assert_eq!(suggest_scale(192.0, DEFAULT_TARGET_DPI), 2.0);
+        assert_eq!(suggest_scale(144.0, DEFAULT_TARGET_DPI), 1.5);
+    }
+
+    #[test]
+    fn suggestions_stay_in_range() {
+        assert_eq!(suggest_scale(72.0, DEFAULT_TARGET_DPI), 1.0);
+        assert_eq!(suggest_scale(500.0, DEFAULT_TARGET_DPI), 4.0);
+    }
+
+    #[test]
+    fn matching_scales_follow_the_reference() {
+        // 101.6 PPI at scale 1
+        let reference = monitor((1920, 1080), 1.0, Some((480, 270)));
+        let monitors = [
+            reference.clone(),
+            // 254 PPI laptop panel
+            monitor((2880, 1800), 1.0, Some((288, 180))),
+            // 50.8 PPI, the same clamp as suggest_scale
+            monitor((1920, 1080), 1.0, Some((960, 540))),
+            monitor((1920, 1080), 1.0, None),
+        ];
+        assert_eq!(
+            matching_scales(&monitors, &reference),
+            [Some(1.0), Some(2.5), Some(1.0), None]
+        );
+
+        let unknown = monitor((1920, 1080), 1.0, None);
+        assert_eq!(matching_scales(&monitors, &unknown), [None; 4]);
+    }
+}
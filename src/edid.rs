@@ -0,0 +1,430 @@
+use std::fs;
+use std::path::PathBuf;
+
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const BLOCK_LEN: usize = 128;
+/// Tag of a CTA-861 extension block, the one carrying HDR and colorimetry data
+const CTA_EXTENSION: u8 = 0x02;
+
+/// PNP ids of common display vendors
+const VENDORS: [(&str, &str); 25] = [
+    ("ACR", "Acer"),
+    ("AOC", "AOC"),
+    ("APP", "Apple"),
+    ("AUO", "AU Optronics"),
+    ("AUS", "ASUS"),
+    ("BNQ", "BenQ"),
+    ("BOE", "BOE"),
+    ("CMN", "Innolux"),
+    ("DEL", "Dell"),
+    ("ENC", "EIZO"),
+    ("GSM", "LG Electronics"),
+    ("HPN", "HP"),
+    ("HWP", "HP"),
+    ("IVM", "iiyama"),
+    ("LEN", "Lenovo"),
+    ("LGD", "LG Display"),
+    ("MSI", "MSI"),
+    ("NEC", "NEC"),
+    ("PHL", "Philips"),
+    ("SAM", "Samsung"),
+    ("SDC", "Samsung Display"),
+    ("SHP", "Sharp"),
+    ("SNY", "Sony"),
+    ("VSC", "ViewSonic"),
+    ("GGL", "Google"),
+];
+
+/// Preferred timing from a detailed timing descriptor
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing {
+    pub width: u16,
+    pub height: u16,
+    pub refresh: f32,
+    /// Image size in millimetres, 0 if unknown
+    pub width_mm: u16,
+    pub height_mm: u16,
+}
+
+/// Monitor range limits, the refresh rates variable refresh can use
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeLimits {
+    pub min_refresh: u16,
+    pub max_refresh: u16,
+    /// Horizontal rates in kHz
+    pub min_hfreq: u16,
+    pub max_hfreq: u16,
+    /// Maximum pixel clock in MHz, 0 if unknown
+    pub max_pixel_clock: u16,
+}
+
+/// HDR static metadata from the CTA-861 extension
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hdr {
+    /// Supported transfer functions, e.g. `PQ`
+    pub eotfs: Vec<&'static str>,
+    /// Desired content luminance in cd/m², if the display says
+    pub max_luminance: Option<f32>,
+    pub max_average_luminance: Option<f32>,
+    pub min_luminance: Option<f32>,
+}
+
+/// Decoded Extended Display Identification Data
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edid {
+    /// Three letter PNP id, e.g. `DEL`
+    pub manufacturer: String,
+    pub product_code: u16,
+    /// Numeric serial from the header, `None` if unset
+    pub serial: Option<u32>,
+    /// Serial number descriptor, usually what is printed on the label
+    pub serial_text: Option<String>,
+    /// Display product name descriptor
+    pub name: Option<String>,
+    /// Unspecified text descriptors, laptop panels keep their model here
+    pub texts: Vec<String>,
+    /// Week 1–54, `None` if unknown or `year` is the model year
+    pub week: Option<u8>,
+    pub year: u16,
+    pub model_year: bool,
+    /// Screen size in centimetres
+    pub size_cm: Option<(u8, u8)>,
+    pub native_timing: Option<Timing>,
+    pub range_limits: Option<RangeLimits>,
+    pub hdr: Option<Hdr>,
+    /// Colour spaces beyond sRGB, e.g. `BT.2020 RGB`
+    pub colorimetry: Vec<&'static str>,
+    /// Raw blob, see [`Self::hash`]
+    pub raw: Vec<u8>,
+}
+
+impl Edid {
+    /// Decodes an EDID blob with its extension blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the base block is truncated, lacks the EDID
+    /// header or fails its checksum. Broken extension blocks are skipped.
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let base = data
+            .get(..BLOCK_LEN)
+            .ok_or_else(|| format!("EDID is {} bytes, expected at least 128", data.len()))?;
+        if base.get(..8) != Some(&HEADER[..]) {
+            return Err("Missing EDID header".to_owned());
+        }
+        if !checksum_ok(base) {
+            return Err("EDID checksum mismatch".to_owned());
+        }
+
+        let id = u16::from_be_bytes([byte(base, 8), byte(base, 9)]);
+        let manufacturer = [10, 5, 0]
+            .iter()
+            .map(|shift| char::from(b'@' + ((id >> shift) & 0x1F) as u8))
+            .collect();
+        let serial = u32::from_le_bytes([
+            byte(base, 12),
+            byte(base, 13),
+            byte(base, 14),
+            byte(base, 15),
+        ]);
+        let week = byte(base, 16);
+        let (width_cm, height_cm) = (byte(base, 21), byte(base, 22));
+
+        let mut edid = Self {
+            manufacturer,
+            product_code: u16::from_le_bytes([byte(base, 10), byte(base, 11)]),
+            serial: (serial != 0).then_some(serial),
+            serial_text: None,
+            name: None,
+            texts: Vec::new(),
+            week: (1..=54).contains(&week).then_some(week),
+            year: 1990 + u16::from(byte(base, 17)),
+            model_year: week == 0xFF,
+            size_cm: (width_cm != 0 && height_cm != 0).then_some((width_cm, height_cm)),
+            native_timing: None,
+            range_limits: None,
+            hdr: None,
+            colorimetry: Vec::new(),
+            raw: data.to_vec(),
+        };
+
+        for descriptor in base.get(54..126).unwrap_or_default().as_chunks::<18>().0 {
+            edid.read_descriptor(descriptor);
+        }
+        for block in data.as_chunks::<BLOCK_LEN>().0.iter().skip(1) {
+            if block.first() == Some(&CTA_EXTENSION) && checksum_ok(block) {
+                edid.read_cta(block);
+            }
+        }
+        Ok(edid)
+    }
+
+    /// FNV-1a hash of the raw blob, stable across runs and Rust versions
+    pub fn hash(&self) -> u64 {
+        self.raw.iter().fold(0xCBF2_9CE4_8422_2325, |hash, b| {
+            (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01B3)
+        })
+    }
+
+    /// Full vendor name, falling back to the PNP id
+    pub fn vendor(&self) -> &str {
+        VENDORS
+            .iter()
+            .find(|(id, _)| *id == self.manufacturer)
+            .map_or(&self.manufacturer, |(_, name)| name)
+    }
+
+    /// Screen diagonal in inches, from the native timing's image size where
+    /// available as it is more precise than the centimetre size
+    pub fn diagonal_inches(&self) -> Option<f32> {
+        let (w, h) = match &self.native_timing {
+            Some(t) if t.width_mm > 0 && t.height_mm > 0 => {
+                (f32::from(t.width_mm), f32::from(t.height_mm))
+            }
+            _ => {
+                let (w, h) = self.size_cm?;
+                (f32::from(w) * 10.0, f32::from(h) * 10.0)
+            }
+        };
+        Some(w.hypot(h) / 25.4)
+    }
+
+    fn read_descriptor(&mut self, d: &[u8]) {
+        let pixel_clock = u16::from_le_bytes([byte(d, 0), byte(d, 1)]);
+        if pixel_clock != 0 {
+            // The first detailed timing is the preferred, native one
+            if self.native_timing.is_none() {
+                self.native_timing = detailed_timing(d);
+            }
+            return;
+        }
+
+        let text = || {
+            let raw = d.get(5..18).unwrap_or_default();
+            let end = raw.iter().position(|&b| b == b'\n').unwrap_or(raw.len());
+            String::from_utf8_lossy(raw.get(..end).unwrap_or_default())
+                .trim()
+                .to_owned()
+        };
+        match byte(d, 3) {
+            0xFF => self.serial_text = Some(text()),
+            0xFC => self.name = Some(text()),
+            0xFE => self.texts.push(text()),
+            0xFD => {
+                // EDID 1.4 adds 255 Hz/kHz to the maximum (0b10) or to
+                // both limits (0b11) for high rates
+                let flags = byte(d, 4);
+                let offset = |bits: u8, min: bool| match (bits & 0b11, min) {
+                    (0b11, _) | (0b10, false) => 255,
+                    _ => 0,
+                };
+                let (vertical, horizontal) = (flags, flags >> 2);
+                self.range_limits = Some(RangeLimits {
+                    min_refresh: u16::from(byte(d, 5)) + offset(vertical, true),
+                    max_refresh: u16::from(byte(d, 6)) + offset(vertical, false),
+                    min_hfreq: u16::from(byte(d, 7)) + offset(horizontal, true),
+                    max_hfreq: u16::from(byte(d, 8)) + offset(horizontal, false),
+                    max_pixel_clock: u16::from(byte(d, 9)) * 10,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Reads the data block collection of a CTA-861 extension
+    fn read_cta(&mut self, block: &[u8]) {
+        let dtd_offset = usize::from(byte(block, 2)).clamp(4, 127);
+        let mut blocks = block.get(4..dtd_offset).unwrap_or_default();
+        while let Some((&header, rest)) = blocks.split_first() {
+            let len = usize::from(header & 0x1F).min(rest.len());
+            let (payload, next) = rest.split_at(len);
+            blocks = next;
+
+            // Extended tag blocks carry the sub-tag in their first byte
+            if header >> 5 != 7 {
+                continue;
+            }
+            match payload.first() {
+                Some(5) => self.colorimetry = colorimetry(payload),
+                Some(6) => self.hdr = Some(hdr(payload)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Locates the EDID of `connector`, e.g. `DP-1`, under `/sys/class/drm`
+pub fn find(connector: &str) -> Option<PathBuf> {
+    fs::read_dir("/sys/class/drm")
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.strip_prefix("card").is_some_and(|rest| {
+                rest.split_once('-').is_some_and(|(card, c)| {
+                    c == connector && card.chars().all(|ch| ch.is_ascii_digit())
+                })
+            })
+        })
+        .map(|entry| entry.path().join("edid"))
+}
+
+/// Reads and decodes the EDID of `connector`, `None` if there is none
+pub fn read(connector: &str) -> Option<Edid> {
+    let data = fs::read(find(connector)?).ok()?;
+    // Disconnected connectors have an empty file
+    if data.is_empty() {
+        return None;
+    }
+    Edid::parse(&data)
+        .inspect_err(|e| log::debug!("Decoding the EDID of {connector} failed: {e}"))
+        .ok()
+}
+
+fn byte(data: &[u8], idx: usize) -> u8 {
+    data.get(idx).copied().unwrap_or_default()
+}
+
+fn checksum_ok(block: &[u8]) -> bool {
+    block.iter().fold(0u8, |sum, b| sum.wrapping_add(*b)) == 0
+}
+
+fn detailed_timing(d: &[u8]) -> Option<Timing> {
+    let high = |idx: usize, shift: u8| u16::from((byte(d, idx) >> shift) & 0x0F) << 8;
+    let width = u16::from(byte(d, 2)) | high(4, 4);
+    let h_blank = u16::from(byte(d, 3)) | high(4, 0);
+    let height = u16::from(byte(d, 5)) | high(7, 4);
+    let v_blank = u16::from(byte(d, 6)) | high(7, 0);
+    let pixel_clock = f32::from(u16::from_le_bytes([byte(d, 0), byte(d, 1)])) * 10_000.0;
+    let total = f32::from(width + h_blank) * f32::from(height + v_blank);
+    if total == 0.0 {
+        return None;
+    }
+    Some(Timing {
+        width,
+        height,
+        refresh: pixel_clock / total,
+        width_mm: u16::from(byte(d, 12)) | high(14, 4),
+        height_mm: u16::from(byte(d, 13)) | high(14, 0),
+    })
+}
+
+fn colorimetry(payload: &[u8]) -> Vec<&'static str> {
+    const NAMES: [&str; 8] = [
+        "xvYCC 601",
+        "xvYCC 709",
+        "sYCC 601",
+        "opYCC 601",
+        "opRGB",
+        "BT.2020 cYCC",
+        "BT.2020 YCC",
+        "BT.2020 RGB",
+    ];
+    let flags = byte(payload, 1);
+    let mut spaces: Vec<&'static str> = NAMES
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| flags & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if byte(payload, 2) & 0x80 != 0 {
+        spaces.push("DCI-P3");
+    }
+    spaces
+}
+
+fn hdr(payload: &[u8]) -> Hdr {
+    const EOTFS: [&str; 4] = ["SDR", "HDR", "PQ", "HLG"];
+    let flags = byte(payload, 1);
+    let eotfs = EOTFS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| flags & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect();
+
+    // Luminance code values as defined by CTA-861.3
+    let luminance = |cv: u8| 50.0 * 2f32.powf(f32::from(cv) / 32.0);
+    let max = payload.get(3).map(|&cv| luminance(cv));
+    Hdr {
+        eotfs,
+        max_luminance: max,
+        max_average_luminance: payload.get(4).map(|&cv| luminance(cv)),
+        min_luminance: max
+            .zip(payload.get(5))
+            .map(|(max, &cv)| max * (f32::from(cv) / 255.0).powi(2) / 100.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTERNAL: &[u8] = include_bytes!("../tests/fixtures/edid-external.bin");
+    const LAPTOP: &[u8] = include_bytes!("../tests/fixtures/edid-laptop.bin");
+
+    #[test]
+    fn decodes_external_monitor() {
+        let edid = Edid::parse(EXTERNAL).expect("valid EDID");
+        assert_eq!(edid.manufacturer, "DEL");
+        assert_eq!(edid.vendor(), "Dell");
+        assert_eq!(edid.product_code, 0xA0F1);
+        assert_eq!(edid.serial, Some(0x3141_5926));
+        assert_eq!(edid.serial_text.as_deref(), Some("ABC1234"));
+        assert_eq!(edid.name.as_deref(), Some("DELL U2723QE"));
+        assert_eq!(
+            (edid.week, edid.year, edid.model_year),
+            (Some(12), 2022, false)
+        );
+        assert_eq!(edid.size_cm, Some((60, 34)));
+
+        let timing = edid.native_timing.as_ref().expect("native timing");
+        assert_eq!((timing.width, timing.height), (3840, 2160));
+        assert!((timing.refresh - 60.0).abs() < 0.01);
+        assert_eq!((timing.width_mm, timing.height_mm), (597, 336));
+
+        let limits = edid.range_limits.expect("range limits");
+        assert_eq!((limits.min_refresh, limits.max_refresh), (40, 60));
+        assert_eq!((limits.min_hfreq, limits.max_hfreq), (30, 140));
+        assert_eq!(limits.max_pixel_clock, 600);
+
+        let hdr = edid.hdr.expect("HDR metadata");
+        assert_eq!(hdr.eotfs, ["SDR", "PQ"]);
+        assert_eq!(hdr.max_luminance, Some(400.0));
+        let min = hdr.min_luminance.expect("min luminance");
+        assert!((min - 0.063).abs() < 0.001);
+        assert_eq!(edid.colorimetry, ["BT.2020 YCC", "BT.2020 RGB", "DCI-P3"]);
+    }
+
+    #[test]
+    fn decodes_laptop_panel() {
+        let edid = Edid::parse(LAPTOP).expect("valid EDID");
+        assert_eq!(edid.manufacturer, "BOE");
+        assert_eq!(edid.product_code, 0x0747);
+        assert_eq!(edid.serial, None);
+        assert_eq!(edid.name, None);
+        assert_eq!(edid.texts, ["BOE CQ", "NV140FHM-N49"]);
+        assert_eq!((edid.week, edid.year), (None, 2020));
+        assert_eq!(edid.range_limits, None);
+        assert_eq!(edid.hdr, None);
+        assert!(edid.colorimetry.is_empty());
+
+        let timing = edid.native_timing.as_ref().expect("native timing");
+        assert_eq!((timing.width, timing.height), (1920, 1080));
+        assert!((timing.refresh - 59.93).abs() < 0.01);
+        let diagonal = edid.diagonal_inches().expect("diagonal");
+        assert!((diagonal - 13.96).abs() < 0.01);
+    }
+
+    #[test]
+    fn rejects_corrupt_edid() {
+        let mut data = LAPTOP.to_vec();
+        if let Some(b) = data.get_mut(20) {
+            *b ^= 0xFF;
+        }
+        assert!(Edid::parse(&data).is_err());
+        assert!(Edid::parse(LAPTOP.get(..100).expect("fixture is 128 bytes")).is_err());
+        assert!(Edid::parse(&[0; 128]).is_err());
+    }
+}
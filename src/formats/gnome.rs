@@ -0,0 +1,232 @@
+use super::Import;
+use crate::models::{Profile, ProfileMode, ProfileOutput, Transform};
+use roxmltree::{Document, Node};
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|n| n.text()).map(str::trim)
+}
+
+fn child_value<T: std::str::FromStr>(node: Node<'_, '_>, name: &str) -> Option<T> {
+    child_text(node, name)?.parse().ok()
+}
+
+/// Line of `node` in `doc`, for warnings
+fn line(doc: &Document<'_>, node: Node<'_, '_>) -> usize {
+    doc.text_pos_at(node.range().start).row as usize
+}
+
+/// Mutter names rotations counterclockwise, like `wl_output` does
+fn parse_transform(node: Node<'_, '_>) -> Option<Transform> {
+    let flipped = child_text(node, "flipped") == Some("yes");
+    let transform = match (child_text(node, "rotation").unwrap_or("normal"), flipped) {
+        ("normal", false) => Transform::Normal,
+        ("left", false) => Transform::Rotate90,
+        ("upside_down", false) => Transform::Rotate180,
+        ("right", false) => Transform::Rotate270,
+        ("normal", true) => Transform::Flipped,
+        ("left", true) => Transform::Flipped90,
+        ("upside_down", true) => Transform::Flipped180,
+        ("right", true) => Transform::Flipped270,
+        _ => return None,
+    };
+    Some(transform)
+}
+
+/// Outputs are matched by connector, GNOME stores PNP vendor IDs where
+/// wlroots reports the full manufacturer name
+fn connector(monitor: Node<'_, '_>) -> Option<String> {
+    child(monitor, "monitorspec")
+        .and_then(|spec| child_text(spec, "connector"))
+        .map(str::to_owned)
+}
+
+fn parse_logical_monitor(
+    doc: &Document<'_>,
+    logical: Node<'_, '_>,
+    import: &mut Import,
+) -> Vec<ProfileOutput> {
+    let position = child_value(logical, "x").zip(child_value(logical, "y"));
+    let scale = child_value(logical, "scale");
+    let primary = child_text(logical, "primary") == Some("yes");
+    let transform = match child(logical, "transform") {
+        Some(node) => {
+            let transform = parse_transform(node);
+            if transform.is_none() {
+                import
+                    .warnings
+                    .push((line(doc, node), "unknown rotation".to_owned()));
+            }
+            transform
+        }
+        None => Some(Transform::Normal),
+    };
+
+    let mut outputs = Vec::new();
+    // Several monitors in one logical monitor mirror each other
+    for monitor in logical.children().filter(|n| n.has_tag_name("monitor")) {
+        let Some(criteria) = connector(monitor) else {
+            import
+                .warnings
+                .push((line(doc, monitor), "monitor without connector".to_owned()));
+            continue;
+        };
+        let mode = child(monitor, "mode").and_then(|mode| {
+            Some(ProfileMode {
+                width: child_value(mode, "width")?,
+                height: child_value(mode, "height")?,
+                refresh: child_value(mode, "rate"),
+            })
+        });
+        outputs.push(ProfileOutput {
+            criteria,
+            enabled: true,
+            mode,
+            position,
+            scale,
+            transform,
+            adaptive_sync: None,
+            primary,
+            id: None,
+        });
+    }
+    outputs
+}
+
+/// Reads every `<configuration>` of a GNOME `monitors.xml` as a profile
+pub fn import(config: &str) -> Import {
+    let mut import = Import::default();
+    let doc = match Document::parse(config) {
+        Ok(doc) => doc,
+        Err(e) => {
+            import
+                .warnings
+                .push((e.pos().row as usize, format!("invalid XML: {e}")));
+            return import;
+        }
+    };
+
+    let configurations = doc
+        .root_element()
+        .children()
+        .filter(|n| n.has_tag_name("configuration"));
+    for (i, configuration) in configurations.enumerate() {
+        let mut outputs = Vec::new();
+        for node in configuration.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "logicalmonitor" => {
+                    outputs.extend(parse_logical_monitor(&doc, node, &mut import));
+                }
+                "disabled" => {
+                    for monitor in node.children().filter(|n| n.has_tag_name("monitorspec")) {
+                        if let Some(criteria) = child_text(monitor, "connector") {
+                            outputs.push(ProfileOutput {
+                                criteria: criteria.to_owned(),
+                                enabled: false,
+                                mode: None,
+                                position: None,
+                                scale: None,
+                                transform: None,
+                                adaptive_sync: None,
+                                primary: false,
+                                id: None,
+                            });
+                        }
+                    }
+                }
+                "layoutmode" => {
+                    if node.text().map(str::trim) == Some("physical") {
+                        import.warnings.push((
+                            line(&doc, node),
+                            "physical layout mode positions are imported as logical pixels"
+                                .to_owned(),
+                        ));
+                    }
+                }
+                other => import
+                    .warnings
+                    .push((line(&doc, node), format!("unsupported element `{other}`"))),
+            }
+        }
+        outputs.sort_by_key(|o| !o.primary);
+        import.profiles.push(Profile {
+            name: format!("GNOME {}", i + 1),
+            outputs,
+            exec: Vec::new(),
+        });
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITORS_XML: &str = include_str!("../../tests/fixtures/monitors.xml");
+
+    #[test]
+    fn imports_every_configuration() {
+        let import = import(MONITORS_XML);
+        let [docked, _] = import.profiles.as_slice() else {
+            panic!("expected one profile per configuration");
+        };
+        assert_eq!(docked.name, "GNOME 1");
+        let criteria: Vec<_> = docked.outputs.iter().map(|o| o.criteria.as_str()).collect();
+        assert_eq!(criteria, ["DP-3", "eDP-1"], "primary output comes first");
+
+        let [external, internal] = docked.outputs.as_slice() else {
+            panic!("expected two outputs");
+        };
+        assert!(external.primary, "DP-3 is primary");
+        assert_eq!(external.position, Some((1805, 0)));
+        assert_eq!(external.scale, Some(1.0));
+        assert_eq!(external.transform, Some(Transform::Rotate90));
+        assert_eq!(
+            external.mode,
+            Some(ProfileMode {
+                width: 3840,
+                height: 2160,
+                refresh: Some(60.0),
+            })
+        );
+
+        assert!(!internal.primary, "eDP-1 is not primary");
+        assert_eq!(internal.scale, Some(1.25));
+        assert_eq!(internal.transform, Some(Transform::Normal));
+    }
+
+    #[test]
+    fn imports_disabled_monitors() {
+        let import = import(MONITORS_XML);
+        let [_, external_only] = import.profiles.as_slice() else {
+            panic!("expected one profile per configuration");
+        };
+        let internal = external_only
+            .outputs
+            .iter()
+            .find(|o| o.criteria == "eDP-1")
+            .expect("disabled eDP-1 is imported");
+        assert!(!internal.enabled, "eDP-1 is disabled");
+        let external = external_only.outputs.first().expect("DP-3 is imported");
+        assert_eq!(external.scale, Some(2.0));
+    }
+
+    #[test]
+    fn reports_unsupported_elements() {
+        let import = import(MONITORS_XML);
+        assert_eq!(
+            import.warnings,
+            [(78, "unsupported element `privacy`".to_owned())]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_xml() {
+        let import = import("<monitors><configuration></monitors>");
+        assert!(import.profiles.is_empty(), "nothing is imported");
+        assert_eq!(import.warnings.len(), 1, "the parse error is reported");
+    }
+}
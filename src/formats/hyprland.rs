@@ -0,0 +1,57 @@
+use super::{alias_comment, format_refresh, named_by_description};
+use crate::models::Monitor;
+
+/// Hyprland matches `desc:` against `make model serial`, connector names are used as is
+fn monitor_name(monitor: &Monitor) -> String {
+    if named_by_description(monitor) {
+        format!("desc:{}", monitor.description())
+    } else {
+        monitor.name.clone()
+    }
+}
+
+/// Turns `layout` into Hyprland `monitor=` rules, meant for a file sourced from hyprland.conf
+pub fn export(layout: &[Monitor]) -> String {
+    let mut out = "# Generated by WayDisplay\n".to_owned();
+
+    for monitor in layout {
+        out.push_str(&alias_comment(monitor, "", "#"));
+        let name = monitor_name(monitor);
+        if !monitor.enabled {
+            out.push_str(&format!("monitor={name},disable\n"));
+            continue;
+        }
+
+        let mode = monitor.current_mode().map_or_else(
+            || "preferred".to_owned(),
+            |m| format!("{}x{}@{}", m.width, m.height, format_refresh(m.refresh)),
+        );
+        let transform = monitor.transform.index();
+        out.push_str(&format!(
+            "monitor={name},{mode},{}x{},{},transform,{transform},vrr,{}\n",
+            monitor.x,
+            monitor.y,
+            monitor.scale,
+            u8::from(monitor.adaptive_sync)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::fixtures::desk;
+
+    #[test]
+    fn exports_monitor_rules() {
+        assert_eq!(
+            export(&desk()),
+            "# Generated by WayDisplay\n\
+             # Desk \"left\"\n\
+             monitor=desc:Dell Inc. DELL U2720Q 8RZ1XC3,3840x2160@60,0x0,1.5,transform,1,vrr,1\n\
+             monitor=eDP-1,2880x1800@60,1440x0,1,transform,0,vrr,0\n\
+             monitor=HDMI-A-1,disable\n"
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use super::{Import, alias_comment, format_refresh, output_criteria};
+use crate::models::{Monitor, Profile, ProfileMode, ProfileOutput, Transform};
+
+/// Turns `layout` into a kanshi `profile` block
+pub fn export(name: &str, layout: &[Monitor]) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .collect();
+
+    let mut out = if name.is_empty() {
+        "profile {\n".to_owned()
+    } else {
+        format!("profile {name} {{\n")
+    };
+
+    for monitor in layout {
+        out.push_str(&alias_comment(monitor, "\t", "#"));
+        out.push_str(&format!("\toutput {}", output_criteria(monitor)));
+        if !monitor.enabled {
+            out.push_str(" disable\n");
+            continue;
+        }
+
+        out.push_str(" enable");
+        if let Some(mode) = monitor.current_mode() {
+            out.push_str(&format!(
+                " mode {}x{}@{}Hz",
+                mode.width,
+                mode.height,
+                format_refresh(mode.refresh)
+            ));
+        }
+        out.push_str(&format!(
+            " position {},{} scale {} transform {} adaptive_sync {}\n",
+            monitor.x,
+            monitor.y,
+            monitor.scale,
+            monitor.transform.name(),
+            if monitor.adaptive_sync { "on" } else { "off" }
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Splits a line into words, keeping double quoted strings together
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => current.extend(chars.next()),
+            '#' if !in_quotes => break,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn parse_mode(mode: &str) -> Option<ProfileMode> {
+    let (size, refresh) = match mode.split_once('@') {
+        Some((size, refresh)) => (size, Some(refresh.trim_end_matches("Hz").parse().ok()?)),
+        None => (mode, None),
+    };
+    let (width, height) = size.split_once('x')?;
+    Some(ProfileMode {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        refresh,
+    })
+}
+
+fn parse_position(position: &str) -> Option<(i32, i32)> {
+    let (x, y) = position.split_once(',')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Parses an `output` directive inside a profile, `args` excludes the keyword
+fn parse_output(
+    args: &[String],
+    line: usize,
+    warnings: &mut Vec<(usize, String)>,
+) -> Option<ProfileOutput> {
+    let Some((criteria, mut rest)) = args.split_first() else {
+        warnings.push((line, "`output` without criteria".to_owned()));
+        return None;
+    };
+
+    let mut output = ProfileOutput {
+        criteria: criteria.clone(),
+        enabled: true,
+        mode: None,
+        position: None,
+        scale: None,
+        transform: None,
+        adaptive_sync: None,
+        primary: false,
+        id: None,
+    };
+
+    while let Some((directive, tail)) = rest.split_first() {
+        rest = tail;
+        let mut value = || {
+            let (value, tail) = rest.split_first()?;
+            rest = tail;
+            Some(value.as_str())
+        };
+        match directive.as_str() {
+            "enable" => output.enabled = true,
+            "disable" => output.enabled = false,
+            "mode" => {
+                let mut mode = value();
+                if mode == Some("--custom") {
+                    mode = value();
+                }
+                match mode.and_then(parse_mode) {
+                    Some(mode) => output.mode = Some(mode),
+                    None => warnings
+                        .push((line, format!("invalid mode `{}`", mode.unwrap_or_default()))),
+                }
+            }
+            "position" => match value().and_then(parse_position) {
+                Some(position) => output.position = Some(position),
+                None => warnings.push((line, "invalid position".to_owned())),
+            },
+            "scale" => match value().and_then(|v| v.parse().ok()) {
+                Some(scale) => output.scale = Some(scale),
+                None => warnings.push((line, "invalid scale".to_owned())),
+            },
+            "transform" => match value().and_then(Transform::from_name) {
+                Some(transform) => output.transform = Some(transform),
+                None => warnings.push((line, "invalid transform".to_owned())),
+            },
+            "adaptive_sync" => match value() {
+                Some("on") => output.adaptive_sync = Some(true),
+                Some("off") => output.adaptive_sync = Some(false),
+                _ => warnings.push((line, "adaptive_sync expects `on` or `off`".to_owned())),
+            },
+            "alias" => {
+                value();
+                warnings.push((line, "output aliases are not supported".to_owned()));
+            }
+            other => warnings.push((line, format!("unsupported output directive `{other}`"))),
+        }
+    }
+    Some(output)
+}
+
+/// Reads the profiles of a kanshi config.
+///
+/// Directives `WayDisplay` has no equivalent for, like `include` or global
+/// `output` defaults, are skipped and reported in [`Import::warnings`].
+pub fn import(config: &str) -> Import {
+    let mut import = Import::default();
+    let mut current: Option<Profile> = None;
+    let mut unnamed = 0;
+
+    for (idx, raw) in config.lines().enumerate() {
+        let line = idx + 1;
+        let tokens = tokenize(raw);
+        let Some((keyword, args)) = tokens.split_first() else {
+            continue;
+        };
+
+        if let Some(profile) = &mut current {
+            match keyword.as_str() {
+                "}" => {
+                    import.profiles.extend(current.take());
+                }
+                "output" => {
+                    if let Some(output) = parse_output(args, line, &mut import.warnings) {
+                        profile.outputs.push(output);
+                    }
+                }
+                "exec" => {
+                    // Keep the command as written, including its quoting
+                    let command = raw.trim_start().trim_start_matches("exec").trim();
+                    profile.exec.push(command.to_owned());
+                }
+                other => import
+                    .warnings
+                    .push((line, format!("unsupported profile directive `{other}`"))),
+            }
+            continue;
+        }
+
+        match (keyword.as_str(), args) {
+            ("profile", [brace]) if brace == "{" => {
+                unnamed += 1;
+                current = Some(Profile {
+                    name: format!("kanshi {unnamed}"),
+                    outputs: Vec::new(),
+                    exec: Vec::new(),
+                });
+            }
+            ("profile", [name, brace]) if brace == "{" => {
+                current = Some(Profile {
+                    name: name.clone(),
+                    outputs: Vec::new(),
+                    exec: Vec::new(),
+                });
+            }
+            ("profile", _) => import
+                .warnings
+                .push((line, "expected `profile [name] {`".to_owned())),
+            (other, _) => import
+                .warnings
+                .push((line, format!("unsupported directive `{other}`"))),
+        }
+    }
+
+    if let Some(profile) = current {
+        import.warnings.push((
+            config.lines().count(),
+            format!("profile `{}` is not closed", profile.name),
+        ));
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::fixtures::{desk, imported};
+
+    const KANSHI_CONFIG: &str = include_str!("../../tests/fixtures/kanshi.config");
+
+    #[test]
+    fn imports_every_profile() {
+        let import = import(KANSHI_CONFIG);
+        let names: Vec<_> = import.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["docked", "kanshi 1", "kanshi 2"]);
+
+        let [docked, ..] = import.profiles.as_slice() else {
+            panic!("expected three profiles");
+        };
+        let [external, internal] = docked.outputs.as_slice() else {
+            panic!("expected two outputs");
+        };
+        assert_eq!(
+            external.criteria, "Dell Inc. DELL U2720Q 8RZ1XC3",
+            "quoted criteria keep their spaces"
+        );
+        assert_eq!(external.position, Some((0, 0)));
+        assert_eq!(external.scale, Some(1.5));
+        assert_eq!(external.transform, None, "unset values are left alone");
+        assert_eq!(internal.criteria, "eDP-1");
+        assert!(!internal.enabled, "eDP-1 is disabled");
+    }
+
+    #[test]
+    fn parses_modes_with_and_without_refresh() {
+        let import = import(KANSHI_CONFIG);
+        let modes: Vec<_> = import
+            .profiles
+            .iter()
+            .flat_map(|p| &p.outputs)
+            .map(|o| o.mode)
+            .collect();
+        let mode = |width, height, refresh| {
+            Some(ProfileMode {
+                width,
+                height,
+                refresh,
+            })
+        };
+        assert_eq!(
+            modes,
+            [
+                mode(3840, 2160, Some(59.997)),
+                None,
+                mode(2880, 1800, None),
+                mode(1920, 1080, Some(60.0)),
+                None,
+                mode(2560, 1600, Some(120.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_exec_commands_as_written() {
+        let import = import(KANSHI_CONFIG);
+        let exec: Vec<_> = import.profiles.iter().map(|p| p.exec.clone()).collect();
+        assert_eq!(
+            exec,
+            [
+                vec![r#"notify-send "Docked" 'at the desk'"#.to_owned()],
+                vec![
+                    "swaymsg workspace 1".to_owned(),
+                    "swaymsg workspace 2".to_owned()
+                ],
+                Vec::new(),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unsupported_directives_by_line() {
+        let import = import(KANSHI_CONFIG);
+        assert_eq!(
+            import.warnings,
+            [
+                (8, "unsupported directive `include`".to_owned()),
+                (13, "output aliases are not supported".to_owned()),
+                (
+                    20,
+                    "unsupported profile directive `max_render_time`".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unclosed_profiles() {
+        let import = import("profile laptop {\n\toutput eDP-1 enable\n");
+        assert!(import.profiles.is_empty(), "nothing is imported");
+        assert_eq!(
+            import.warnings,
+            [(2, "profile `laptop` is not closed".to_owned())]
+        );
+    }
+
+    #[test]
+    fn round_trips_an_exported_layout() {
+        let import = import(&export("at the desk", &desk()));
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+        let [profile] = import.profiles.as_slice() else {
+            panic!("expected one profile");
+        };
+        assert_eq!(profile.name, "at-the-desk");
+        assert_eq!(
+            profile.outputs,
+            [
+                imported(
+                    "Dell Inc. DELL U2720Q 8RZ1XC3",
+                    (3840, 2160),
+                    (0, 0),
+                    1.5,
+                    Transform::Rotate90,
+                    true
+                ),
+                imported(
+                    "eDP-1",
+                    (2880, 1800),
+                    (1440, 0),
+                    1.0,
+                    Transform::Normal,
+                    false
+                ),
+                ProfileOutput {
+                    criteria: "HDMI-A-1".to_owned(),
+                    enabled: false,
+                    mode: None,
+                    position: None,
+                    scale: None,
+                    transform: None,
+                    adaptive_sync: None,
+                    primary: false,
+                    id: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use super::Import;
+use crate::models::{Profile, ProfileMode, ProfileOutput, Transform};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Size {
+    width: i32,
+    height: i32,
+}
+
+#[derive(Deserialize)]
+struct KscreenMode {
+    refresh: Option<f32>,
+    size: Size,
+}
+
+#[derive(Deserialize)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    /// Connector name
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct KscreenOutput {
+    metadata: Metadata,
+    #[serde(default)]
+    enabled: bool,
+    mode: Option<KscreenMode>,
+    pos: Option<Position>,
+    scale: Option<f32>,
+    /// Bit flags: 1 none, 2 left, 4 inverted, 8 right
+    rotation: Option<u32>,
+    /// Older configs, replaced by `priority`
+    #[serde(default)]
+    primary: bool,
+    /// 1 is the primary output
+    priority: Option<u32>,
+    /// 0 never, 1 always, 2 automatic
+    vrrpolicy: Option<u32>,
+}
+
+/// `KScreen` rotates counterclockwise, like `wl_output` does
+fn parse_rotation(rotation: u32) -> Option<Transform> {
+    match rotation {
+        1 => Some(Transform::Normal),
+        2 => Some(Transform::Rotate90),
+        4 => Some(Transform::Rotate180),
+        8 => Some(Transform::Rotate270),
+        _ => None,
+    }
+}
+
+/// Reads a `KScreen` config file, `name` is used for the resulting profile
+pub fn import(config: &str, name: &str) -> Import {
+    let mut import = Import::default();
+    let outputs: Vec<KscreenOutput> = match serde_json::from_str(config) {
+        Ok(outputs) => outputs,
+        Err(e) => {
+            import
+                .warnings
+                .push((e.line(), format!("invalid KScreen config: {e}")));
+            return import;
+        }
+    };
+
+    let mut profile = Profile {
+        name: format!("KDE {name}"),
+        outputs: Vec::new(),
+        exec: Vec::new(),
+    };
+    for output in outputs {
+        let transform = output.rotation.and_then(|rotation| {
+            let transform = parse_rotation(rotation);
+            if transform.is_none() {
+                import.warnings.push((
+                    0,
+                    format!("{}: unknown rotation {rotation}", output.metadata.name),
+                ));
+            }
+            transform
+        });
+        profile.outputs.push(ProfileOutput {
+            criteria: output.metadata.name,
+            enabled: output.enabled,
+            mode: output.mode.map(|mode| ProfileMode {
+                width: mode.size.width,
+                height: mode.size.height,
+                refresh: mode.refresh,
+            }),
+            position: output.pos.map(|pos| (pos.x, pos.y)),
+            scale: output.scale,
+            transform,
+            // Automatic VRR only kicks in for fullscreen apps, closest to off
+            adaptive_sync: output.vrrpolicy.map(|policy| policy == 1),
+            primary: output.primary || output.priority == Some(1),
+            id: None,
+        });
+    }
+    profile.outputs.sort_by_key(|o| !o.primary);
+    import.profiles.push(profile);
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KSCREEN_JSON: &str = include_str!("../../tests/fixtures/kscreen.json");
+
+    #[test]
+    fn imports_outputs() {
+        let import = import(KSCREEN_JSON, "9f8e7d6c");
+        let [profile] = import.profiles.as_slice() else {
+            panic!("expected one profile per file");
+        };
+        assert_eq!(profile.name, "KDE 9f8e7d6c");
+        let criteria: Vec<_> = profile
+            .outputs
+            .iter()
+            .map(|o| o.criteria.as_str())
+            .collect();
+        assert_eq!(
+            criteria,
+            ["DP-3", "eDP-1", "HDMI-A-1"],
+            "primary output comes first"
+        );
+
+        let [external, internal, _] = profile.outputs.as_slice() else {
+            panic!("expected three outputs");
+        };
+        assert!(external.primary, "priority 1 is primary");
+        assert!(external.enabled, "DP-3 is enabled");
+        assert_eq!(external.position, Some((1805, 0)));
+        assert_eq!(external.scale, Some(1.5));
+        assert_eq!(external.transform, Some(Transform::Rotate270));
+        assert_eq!(external.adaptive_sync, Some(true));
+        assert_eq!(
+            external.mode,
+            Some(ProfileMode {
+                width: 3840,
+                height: 2160,
+                refresh: Some(60.0),
+            })
+        );
+
+        assert!(!internal.primary, "priority 2 is not primary");
+        assert_eq!(internal.scale, Some(1.25));
+        assert_eq!(internal.transform, Some(Transform::Normal));
+        assert_eq!(internal.adaptive_sync, Some(false));
+    }
+
+    #[test]
+    fn imports_disabled_outputs() {
+        let import = import(KSCREEN_JSON, "test");
+        let hdmi = import
+            .profiles
+            .first()
+            .and_then(|p| p.outputs.last())
+            .expect("HDMI-A-1 is imported");
+        assert!(!hdmi.enabled, "HDMI-A-1 is disabled");
+        assert_eq!(hdmi.mode, None);
+        assert_eq!(hdmi.transform, None);
+        assert_eq!(
+            import.warnings,
+            [(0, "HDMI-A-1: unknown rotation 16".to_owned())]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_json() {
+        let import = import("{\n\"outputs\": 1\n}", "test");
+        assert!(import.profiles.is_empty(), "nothing is imported");
+        assert_eq!(import.warnings.len(), 1, "the parse error is reported");
+    }
+}
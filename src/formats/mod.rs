@@ -0,0 +1,328 @@
+pub mod gnome;
+pub mod hyprland;
+pub mod kanshi;
+pub mod kscreen;
+pub mod niri;
+pub mod script;
+pub mod sway;
+
+use crate::models::{Monitor, Profile};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Configuration formats the current layout can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Kanshi,
+    Sway,
+    Hyprland,
+    Niri,
+    /// Standalone shell script calling wlr-randr
+    Script,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 5] = [
+        Self::Kanshi,
+        Self::Sway,
+        Self::Hyprland,
+        Self::Niri,
+        Self::Script,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Kanshi => "kanshi",
+            Self::Sway => "Sway",
+            Self::Hyprland => "Hyprland",
+            Self::Niri => "niri",
+            Self::Script => "Shell Script",
+        }
+    }
+
+    /// Where the export is saved unless the user picks another file
+    pub fn default_path(self) -> PathBuf {
+        match self {
+            Self::Kanshi => config_dir().join("kanshi").join("waydisplay"),
+            Self::Sway => config_dir().join("sway").join("outputs"),
+            Self::Hyprland => config_dir().join("hypr").join("monitors.conf"),
+            Self::Niri => config_dir().join("niri").join("outputs.kdl"),
+            Self::Script => home_dir()
+                .join(".local")
+                .join("bin")
+                .join("waydisplay-layout.sh"),
+        }
+    }
+
+    /// Note shown next to the save button, e.g. how to load the saved file
+    pub fn save_hint(self) -> &'static str {
+        match self {
+            Self::Kanshi => "Add `include ~/.config/kanshi/waydisplay` to your kanshi config.",
+            Self::Sway => "Add `include ~/.config/sway/outputs` to your sway config.",
+            Self::Hyprland => "Add `source = ~/.config/hypr/monitors.conf` to your hyprland.conf.",
+            Self::Niri => "Add `include \"outputs.kdl\"` to your niri config.kdl.",
+            Self::Script => "Run the script from your compositor's autostart.",
+        }
+    }
+
+    /// Whether the format groups outputs into named profiles
+    pub fn has_profiles(self) -> bool {
+        match self {
+            Self::Kanshi => true,
+            Self::Sway | Self::Hyprland | Self::Niri | Self::Script => false,
+        }
+    }
+
+    /// Renders `layout` in this format, `name` names the profile where the format has them
+    pub fn export(self, name: &str, layout: &[Monitor]) -> String {
+        match self {
+            Self::Kanshi => kanshi::export(name, layout),
+            Self::Sway => sway::export(layout),
+            Self::Hyprland => hyprland::export(layout),
+            Self::Niri => niri::export(layout),
+            Self::Script => script::export(layout),
+        }
+    }
+
+    /// Writes an export to `path`, scripts are made executable
+    pub fn save(self, path: &Path, contents: &str) -> io::Result<()> {
+        save(path, contents)?;
+        #[cfg(unix)]
+        if self == Self::Script {
+            use std::os::unix::fs::PermissionsExt as _;
+            let mut permissions = fs::metadata(path)?.permissions();
+            permissions.set_mode(0o755);
+            fs::set_permissions(path, permissions)?;
+        }
+        Ok(())
+    }
+}
+
+/// Configuration formats layouts can be imported from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    Kanshi,
+    Niri,
+    Gnome,
+    Kscreen,
+}
+
+impl ImportFormat {
+    pub const ALL: [Self; 4] = [Self::Kanshi, Self::Niri, Self::Gnome, Self::Kscreen];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Kanshi => "kanshi",
+            Self::Niri => "niri",
+            Self::Gnome => "GNOME",
+            Self::Kscreen => "KDE",
+        }
+    }
+
+    /// Where the format usually keeps its configuration, a directory means
+    /// every file in it is imported
+    pub fn default_path(self) -> PathBuf {
+        match self {
+            Self::Kanshi => config_dir().join("kanshi").join("config"),
+            Self::Niri => config_dir().join("niri").join("config.kdl"),
+            Self::Gnome => config_dir().join("monitors.xml"),
+            Self::Kscreen => data_dir().join("kscreen"),
+        }
+    }
+
+    /// Parses `config`, `file_name` names profiles in formats that have one layout per file
+    pub fn import(self, config: &str, file_name: &str) -> Import {
+        match self {
+            Self::Kanshi => kanshi::import(config),
+            Self::Niri => niri::import(config),
+            Self::Gnome => gnome::import(config),
+            Self::Kscreen => kscreen::import(config, file_name),
+        }
+    }
+}
+
+/// Profiles read from a foreign config, plus everything that could not be imported
+#[derive(Default)]
+pub struct Import {
+    pub profiles: Vec<Profile>,
+    /// 1-based line number, 0 if the problem isn't tied to a line, and what was wrong
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// Whether a config can name `monitor` by its description: the EDID has any
+/// of make, model or serial, and no other connected output has the same
+pub fn named_by_description(monitor: &Monitor) -> bool {
+    monitor.has_identity() && !monitor.shared_id
+}
+
+/// How kanshi and sway identify `monitor`: its quoted description if
+/// [`named_by_description`], otherwise the connector name
+pub fn output_criteria(monitor: &Monitor) -> String {
+    if named_by_description(monitor) {
+        format!("\"{}\"", monitor.description())
+    } else {
+        monitor.name.clone()
+    }
+}
+
+/// A comment line naming `monitor` by its alias, empty if it has none
+pub fn alias_comment(monitor: &Monitor, indent: &str, marker: &str) -> String {
+    monitor.alias.as_ref().map_or_else(String::new, |alias| {
+        format!("{indent}{marker} {}\n", alias.replace('\n', " "))
+    })
+}
+
+/// `$XDG_CONFIG_HOME`, falling back to `~/.config`
+pub fn config_dir() -> PathBuf {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home_dir().join(".config"))
+}
+
+/// `$XDG_DATA_HOME`, falling back to `~/.local/share`
+pub fn data_dir() -> PathBuf {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home_dir().join(".local").join("share"))
+}
+
+pub fn home_dir() -> PathBuf {
+    env::var_os("HOME").map(PathBuf::from).unwrap_or_default()
+}
+
+/// Expands a leading `~/` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home_dir().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Writes `contents` to `path`, creating missing parent directories
+pub fn save(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+/// Refresh rate in Hz without trailing zeros, e.g. `59.951` or `60`
+pub fn format_refresh(refresh: f32) -> String {
+    let formatted = format!("{refresh:.3}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_owned()
+}
+
+/// Layouts the exporter and importer tests share
+#[cfg(test)]
+pub mod fixtures {
+    use crate::models::{Monitor, ProfileMode, ProfileOutput, Transform, mark_shared_ids};
+
+    /// A rotated Dell with a serial and an alias, the laptop panel to its
+    /// right and a disabled projector
+    pub fn desk() -> [Monitor; 3] {
+        let external = Monitor {
+            make: "Dell Inc.".to_owned(),
+            model: "DELL U2720Q".to_owned(),
+            serial: Some("8RZ1XC3".to_owned()),
+            scale: 1.5,
+            transform: Transform::Rotate90,
+            adaptive_sync: true,
+            alias: Some("Desk \"left\"".to_owned()),
+            ..Monitor::with_mode("DP-1", (3840, 2160))
+        };
+        let laptop = Monitor {
+            x: 1440,
+            ..Monitor::with_mode("eDP-1", (2880, 1800))
+        };
+        let projector = Monitor {
+            enabled: false,
+            ..Monitor::with_mode("HDMI-A-1", (1920, 1080))
+        };
+        [external, laptop, projector]
+    }
+
+    /// Two monitors of the same model without a serial side by side on DP-1
+    /// and DP-2, both reporting `edid_hash`
+    pub fn identical_pair(edid_hash: Option<u64>) -> [Monitor; 2] {
+        let monitor = |name: &str, x| Monitor {
+            make: "Dell Inc.".to_owned(),
+            model: "DELL P2419H".to_owned(),
+            edid_hash,
+            x,
+            ..Monitor::with_mode(name, (1920, 1080))
+        };
+        let mut pair = [monitor("DP-1", 0), monitor("DP-2", 1920)];
+        mark_shared_ids(&mut pair);
+        pair
+    }
+
+    /// An enabled output as importing [`desk`] gives it, at 60 Hz
+    pub fn imported(
+        criteria: &str,
+        (width, height): (i32, i32),
+        position: (i32, i32),
+        scale: f32,
+        transform: Transform,
+        adaptive_sync: bool,
+    ) -> ProfileOutput {
+        ProfileOutput {
+            criteria: criteria.to_owned(),
+            enabled: true,
+            mode: Some(ProfileMode {
+                width,
+                height,
+                refresh: Some(60.0),
+            }),
+            position: Some(position),
+            scale: Some(scale),
+            transform: Some(transform),
+            adaptive_sync: Some(adaptive_sync),
+            primary: false,
+            id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{desk, identical_pair};
+    use super::*;
+
+    #[test]
+    fn outputs_are_named_by_description_or_connector() {
+        let [external, laptop, _] = desk();
+        assert_eq!(
+            output_criteria(&external),
+            "\"Dell Inc. DELL U2720Q 8RZ1XC3\""
+        );
+        assert_eq!(output_criteria(&laptop), "eDP-1", "no EDID identity");
+    }
+
+    #[test]
+    fn identical_monitors_are_named_by_connector() {
+        for edid_hash in [Some(0x5eed), None] {
+            let [left, right] = identical_pair(edid_hash);
+            assert_eq!(
+                [output_criteria(&left), output_criteria(&right)],
+                ["DP-1", "DP-2"],
+                "EDID hash {edid_hash:?}"
+            );
+        }
+
+        // Alone, make and model name it on any connector
+        let [alone, _] = identical_pair(None);
+        let alone = Monitor {
+            shared_id: false,
+            ..alone
+        };
+        assert!(named_by_description(&alone));
+        assert_eq!(output_criteria(&alone), "\"Dell Inc. DELL P2419H Unknown\"");
+    }
+}
@@ -0,0 +1,386 @@
+use super::{Import, alias_comment, named_by_description};
+use crate::models::{Monitor, Profile, ProfileMode, ProfileOutput, Transform};
+
+/// niri matches outputs by connector or by `make model serial`
+fn output_name(monitor: &Monitor) -> String {
+    if named_by_description(monitor) {
+        monitor.description()
+    } else {
+        monitor.name.clone()
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Turns `layout` into niri `output` blocks
+pub fn export(layout: &[Monitor]) -> String {
+    let mut out = "// Generated by WayDisplay\n".to_owned();
+
+    for monitor in layout {
+        out.push_str(&alias_comment(monitor, "", "//"));
+        out.push_str(&format!("output {} {{\n", quote(&output_name(monitor))));
+        if !monitor.enabled {
+            out.push_str("    off\n}\n");
+            continue;
+        }
+
+        if let Some(mode) = monitor.current_mode() {
+            out.push_str(&format!(
+                "    mode \"{}x{}@{:.3}\"\n",
+                mode.width, mode.height, mode.refresh
+            ));
+        }
+        out.push_str(&format!("    scale {}\n", monitor.scale));
+        out.push_str(&format!("    transform \"{}\"\n", monitor.transform.name()));
+        out.push_str(&format!("    position x={} y={}\n", monitor.x, monitor.y));
+        if monitor.adaptive_sync {
+            out.push_str("    variable-refresh-rate\n");
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// Bare identifier, number or `key=value` property
+    Word(String),
+    Str(String),
+    Open,
+    Close,
+    /// `;` or a line break
+    End,
+    /// `/-`, comments out the following node
+    SlashDash,
+}
+
+/// Splits KDL into tokens paired with their 1-based line number
+fn tokenize(config: &str) -> Vec<(usize, Token)> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = config.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                tokens.push((line, Token::End));
+                line += 1;
+            }
+            ';' => tokens.push((line, Token::End)),
+            '{' => tokens.push((line, Token::Open)),
+            '}' => tokens.push((line, Token::Close)),
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|c| *c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'-') => {
+                chars.next();
+                tokens.push((line, Token::SlashDash));
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' => tokens.push((line, Token::Str(read_string(&mut chars)))),
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !matches!(c, ';' | '{' | '}' | '"'))
+                {
+                    word.push(c);
+                }
+                // `key="value"` properties
+                if word.ends_with('=') && chars.next_if_eq(&'"').is_some() {
+                    word.push_str(&read_string(&mut chars));
+                }
+                tokens.push((line, Token::Word(word)));
+            }
+        }
+    }
+    tokens
+}
+
+fn read_string(chars: &mut impl Iterator<Item = char>) -> String {
+    let mut s = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => s.push('\n'),
+                Some(c) => s.push(c),
+                None => {}
+            },
+            c => s.push(c),
+        }
+    }
+    s
+}
+
+/// A KDL node with its arguments as plain strings
+struct Node {
+    line: usize,
+    name: String,
+    args: Vec<String>,
+    children: Vec<Self>,
+}
+
+fn parse_nodes(
+    tokens: &mut std::iter::Peekable<impl Iterator<Item = (usize, Token)>>,
+) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut skip_next = false;
+
+    while let Some((line, token)) = tokens.next() {
+        let name = match token {
+            Token::Close => break,
+            Token::End | Token::Open => continue,
+            Token::SlashDash => {
+                skip_next = true;
+                continue;
+            }
+            Token::Word(name) | Token::Str(name) => name,
+        };
+
+        let mut node = Node {
+            line,
+            name,
+            args: Vec::new(),
+            children: Vec::new(),
+        };
+        while let Some((_, token)) =
+            tokens.next_if(|(_, t)| !matches!(t, Token::End | Token::Close))
+        {
+            match token {
+                Token::Word(arg) | Token::Str(arg) => node.args.push(arg),
+                Token::Open => {
+                    node.children = parse_nodes(tokens);
+                    break;
+                }
+                Token::SlashDash => {
+                    // Drop the commented out argument
+                    tokens.next();
+                }
+                Token::End | Token::Close => {}
+            }
+        }
+
+        if !std::mem::take(&mut skip_next) {
+            nodes.push(node);
+        }
+    }
+    nodes
+}
+
+fn parse_mode(mode: &str) -> Option<ProfileMode> {
+    let (size, refresh) = match mode.split_once('@') {
+        Some((size, refresh)) => (size, Some(refresh.parse().ok()?)),
+        None => (mode, None),
+    };
+    let (width, height) = size.split_once('x')?;
+    Some(ProfileMode {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        refresh,
+    })
+}
+
+fn parse_position(args: &[String]) -> Option<(i32, i32)> {
+    let mut x = None;
+    let mut y = None;
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("x", value)) => x = value.parse().ok(),
+            Some(("y", value)) => y = value.parse().ok(),
+            _ => return None,
+        }
+    }
+    Some((x?, y?))
+}
+
+fn parse_output(node: &Node, warnings: &mut Vec<(usize, String)>) -> Option<ProfileOutput> {
+    let Some(criteria) = node.args.first() else {
+        warnings.push((node.line, "`output` without a name".to_owned()));
+        return None;
+    };
+
+    let mut output = ProfileOutput {
+        criteria: criteria.clone(),
+        enabled: true,
+        mode: None,
+        position: None,
+        scale: None,
+        transform: None,
+        adaptive_sync: Some(false),
+        primary: false,
+        id: None,
+    };
+
+    for child in &node.children {
+        let value = child.args.first().map(String::as_str);
+        match child.name.as_str() {
+            "off" => output.enabled = false,
+            "mode" => match value.and_then(parse_mode) {
+                Some(mode) => output.mode = Some(mode),
+                None => warnings.push((child.line, "invalid mode".to_owned())),
+            },
+            "scale" => match value.and_then(|v| v.parse().ok()) {
+                Some(scale) => output.scale = Some(scale),
+                None => warnings.push((child.line, "invalid scale".to_owned())),
+            },
+            "transform" => match value.and_then(Transform::from_name) {
+                Some(transform) => output.transform = Some(transform),
+                None => warnings.push((child.line, "invalid transform".to_owned())),
+            },
+            "position" => match parse_position(&child.args) {
+                Some(position) => output.position = Some(position),
+                None => warnings.push((child.line, "invalid position".to_owned())),
+            },
+            "variable-refresh-rate" => {
+                if !child.args.is_empty() {
+                    warnings.push((
+                        child.line,
+                        "on-demand variable refresh rate is imported as always on".to_owned(),
+                    ));
+                }
+                output.adaptive_sync = Some(true);
+            }
+            other => warnings.push((child.line, format!("unsupported output setting `{other}`"))),
+        }
+    }
+    Some(output)
+}
+
+/// Reads the `output` blocks of a niri config into a single profile.
+///
+/// All other top-level nodes are configuration unrelated to outputs and are ignored.
+pub fn import(config: &str) -> Import {
+    let mut import = Import::default();
+    let mut tokens = tokenize(config).into_iter().peekable();
+
+    let outputs: Vec<ProfileOutput> = parse_nodes(&mut tokens)
+        .iter()
+        .filter(|node| node.name == "output")
+        .filter_map(|node| parse_output(node, &mut import.warnings))
+        .collect();
+
+    if !outputs.is_empty() {
+        import.profiles.push(Profile {
+            name: "niri".to_owned(),
+            outputs,
+            exec: Vec::new(),
+        });
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::fixtures::{desk, imported};
+
+    #[test]
+    fn round_trips_an_exported_layout() {
+        let import = import(&export(&desk()));
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+        let [profile] = import.profiles.as_slice() else {
+            panic!("expected one profile");
+        };
+
+        assert_eq!(
+            profile.outputs,
+            [
+                imported(
+                    "Dell Inc. DELL U2720Q 8RZ1XC3",
+                    (3840, 2160),
+                    (0, 0),
+                    1.5,
+                    Transform::Rotate90,
+                    true
+                ),
+                imported(
+                    "eDP-1",
+                    (2880, 1800),
+                    (1440, 0),
+                    1.0,
+                    Transform::Normal,
+                    false
+                ),
+                ProfileOutput {
+                    criteria: "HDMI-A-1".to_owned(),
+                    enabled: false,
+                    mode: None,
+                    position: None,
+                    scale: None,
+                    transform: None,
+                    adaptive_sync: Some(false),
+                    primary: false,
+                    id: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_slashdash_comments() {
+        let import = import(
+            r#"/-output "DP-2" {
+    scale 2
+}
+output "eDP-1" {
+    /-mode "1920x1080@60"
+    scale /-1.5 2; transform "flipped-90"
+}
+"#,
+        );
+        let [profile] = import.profiles.as_slice() else {
+            panic!("expected one profile");
+        };
+        let [output] = profile.outputs.as_slice() else {
+            panic!("the commented out output is skipped");
+        };
+        assert_eq!(output.criteria, "eDP-1");
+        assert_eq!(output.mode, None, "the commented out mode is skipped");
+        assert_eq!(
+            output.scale,
+            Some(2.0),
+            "the commented out argument is skipped"
+        );
+        assert_eq!(output.transform, Some(Transform::Flipped90));
+    }
+
+    #[test]
+    fn reports_unsupported_settings_by_line() {
+        let import = import(
+            r#"output "eDP-1" {
+    mode "1920x1080@60"
+    focus-at-startup
+    variable-refresh-rate on-demand=true
+    position x=0
+}
+"#,
+        );
+        assert_eq!(
+            import.warnings,
+            [
+                (
+                    3,
+                    "unsupported output setting `focus-at-startup`".to_owned()
+                ),
+                (
+                    4,
+                    "on-demand variable refresh rate is imported as always on".to_owned()
+                ),
+                (5, "invalid position".to_owned()),
+            ]
+        );
+    }
+}
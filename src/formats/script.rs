@@ -0,0 +1,121 @@
+use super::alias_comment;
+use crate::backend::{Backend, OutputConfig};
+use crate::models::Monitor;
+
+/// Quotes `arg` for POSIX shells unless it only has safe characters
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.,@:/=+".contains(c));
+    if safe {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Turns `layout` into a shell script applying it with wlr-randr.
+///
+/// Outputs that aren't connected are skipped, the rest is applied with a
+/// single wlr-randr call so the compositor sees the whole layout at once.
+pub fn export(layout: &[Monitor]) -> String {
+    let mut out = String::from(
+        r#"#!/bin/sh
+# Generated by WayDisplay, applies a display layout with wlr-randr
+set -u
+
+if ! command -v wlr-randr >/dev/null 2>&1; then
+    echo "wlr-randr is not installed, cannot apply the display layout" >&2
+    exit 1
+fi
+
+if ! outputs=$(wlr-randr); then
+    echo "wlr-randr failed, is this a Wayland session with wlr-output-management?" >&2
+    exit 1
+fi
+
+has_output() {
+    printf '%s\n' "$outputs" | grep -q "^$1 "
+}
+
+set --
+"#,
+    );
+
+    for monitor in layout {
+        // Disabled outputs are turned off whatever their mode
+        let mode = monitor.current_mode();
+        if monitor.enabled && mode.is_none() {
+            continue;
+        }
+        let config = OutputConfig {
+            name: &monitor.name,
+            enabled: monitor.enabled,
+            mode,
+            position: Some((monitor.x, monitor.y)),
+            scale: monitor.scale,
+            transform: monitor.transform,
+            adaptive_sync: monitor.adaptive_sync,
+        };
+        let args = Backend::WlrRandr
+            .apply_commands(&config)
+            .iter()
+            .flat_map(|cmd| {
+                cmd.get_args()
+                    .map(|a| shell_quote(&a.to_string_lossy()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let name = shell_quote(&monitor.name);
+        out.push('\n');
+        out.push_str(&alias_comment(monitor, "", "#"));
+        out.push_str(&format!(
+            "# {}\nif has_output {name}; then\n    set -- \"$@\" {args}\nelse\n    echo {} >&2\nfi\n",
+            monitor.description(),
+            shell_quote(&format!("{} is not connected, skipping it", monitor.name)),
+        ));
+    }
+
+    out.push_str(
+        r#"
+if [ $# -eq 0 ]; then
+    echo "None of the outputs in this layout are connected, nothing to do" >&2
+    exit 1
+fi
+
+exec wlr-randr "$@"
+"#,
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_off_disabled_outputs_without_a_mode() {
+        let projector = Monitor {
+            enabled: false,
+            modes: Vec::new(),
+            ..Monitor::with_mode("HDMI-A-1", (1920, 1080))
+        };
+        let script = export(&[Monitor::with_mode("eDP-1", (1920, 1080)), projector]);
+        assert!(
+            script.contains(r#"set -- "$@" --output HDMI-A-1 --off"#),
+            "{script}"
+        );
+    }
+
+    #[test]
+    fn skips_enabled_outputs_without_a_mode() {
+        let laptop = Monitor {
+            modes: Vec::new(),
+            ..Monitor::with_mode("eDP-1", (1920, 1080))
+        };
+        assert!(!export(&[laptop]).contains("--output eDP-1"));
+    }
+}
@@ -0,0 +1,53 @@
+use super::{alias_comment, format_refresh, output_criteria};
+use crate::models::Monitor;
+
+/// Turns `layout` into sway `output` commands, meant for a file included from the sway config
+pub fn export(layout: &[Monitor]) -> String {
+    let mut out = "# Generated by WayDisplay\n".to_owned();
+
+    for monitor in layout {
+        out.push_str(&alias_comment(monitor, "", "#"));
+        out.push_str(&format!("output {}", output_criteria(monitor)));
+        if !monitor.enabled {
+            out.push_str(" disable\n");
+            continue;
+        }
+
+        out.push_str(" enable");
+        if let Some(mode) = monitor.current_mode() {
+            out.push_str(&format!(
+                " mode {}x{}@{}Hz",
+                mode.width,
+                mode.height,
+                format_refresh(mode.refresh)
+            ));
+        }
+        out.push_str(&format!(
+            " pos {} {} scale {} transform {} adaptive_sync {}\n",
+            monitor.x,
+            monitor.y,
+            monitor.scale,
+            monitor.transform.name(),
+            if monitor.adaptive_sync { "on" } else { "off" }
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::fixtures::desk;
+
+    #[test]
+    fn exports_output_commands() {
+        assert_eq!(
+            export(&desk()),
+            "# Generated by WayDisplay\n\
+             # Desk \"left\"\n\
+             output \"Dell Inc. DELL U2720Q 8RZ1XC3\" enable mode 3840x2160@60Hz pos 0 0 scale 1.5 transform 90 adaptive_sync on\n\
+             output eDP-1 enable mode 2880x1800@60Hz pos 1440 0 scale 1 transform normal adaptive_sync off\n\
+             output HDMI-A-1 disable\n"
+        );
+    }
+}
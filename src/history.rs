@@ -0,0 +1,223 @@
+use crate::models::Monitor;
+use std::time::{Duration, Instant};
+
+/// Edits kept for undo, older ones are dropped
+const MAX_EDITS: usize = 100;
+/// Consecutive edits with the same label this close together are undone as one,
+/// e.g. typing a scale digit by digit
+const MERGE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Configuration of all outputs at one point in the session
+#[derive(Clone)]
+pub struct Snapshot {
+    pub label: String,
+    pub monitors: Vec<Monitor>,
+    pub time: Instant,
+}
+
+impl Snapshot {
+    pub fn new(label: String, monitors: Vec<Monitor>) -> Self {
+        Self {
+            label,
+            monitors,
+            time: Instant::now(),
+        }
+    }
+}
+
+/// Undo and redo over the pending configuration, plus every configuration
+/// applied during the session
+#[derive(Default)]
+pub struct History {
+    /// Configuration before each edit, oldest first
+    undo: Vec<Snapshot>,
+    /// Configuration before each undo, the next edit to redo last
+    redo: Vec<Snapshot>,
+    applied: Vec<Snapshot>,
+}
+
+impl History {
+    /// Records the edit `label`, `before` is the pending configuration it started from
+    pub fn record(&mut self, label: String, before: Vec<Monitor>) {
+        self.record_at(label, before, Instant::now());
+    }
+
+    /// [`Self::record`] for an edit made at `now`
+    fn record_at(&mut self, label: String, before: Vec<Monitor>, now: Instant) {
+        self.redo.clear();
+        if let Some(last) = self.undo.last_mut()
+            && last.label == label
+            && now.saturating_duration_since(last.time) < MERGE_WINDOW
+        {
+            last.time = now;
+            return;
+        }
+
+        self.undo.push(Snapshot {
+            label,
+            monitors: before,
+            time: now,
+        });
+        if self.undo.len() > MAX_EDITS {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Steps back one edit, returning the configuration to restore
+    pub fn undo(&mut self, current: Vec<Monitor>) -> Option<Vec<Monitor>> {
+        let snapshot = self.undo.pop()?;
+        self.redo.push(Snapshot::new(snapshot.label, current));
+        Some(snapshot.monitors)
+    }
+
+    /// Repeats the last undone edit, returning the configuration to restore
+    pub fn redo(&mut self, current: Vec<Monitor>) -> Option<Vec<Monitor>> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push(Snapshot::new(snapshot.label, current));
+        Some(snapshot.monitors)
+    }
+
+    /// Forgets undo and redo, e.g. once the edited outputs are gone
+    pub fn clear_edits(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Remembers `monitors` as a configuration the backend accepted
+    pub fn record_applied(&mut self, label: String, monitors: Vec<Monitor>) {
+        self.applied.push(Snapshot::new(label, monitors));
+    }
+
+    /// Edits that can be undone, oldest first
+    pub fn edits(&self) -> &[Snapshot] {
+        &self.undo
+    }
+
+    /// Undone edits that can be redone, the next one last
+    pub fn undone(&self) -> &[Snapshot] {
+        &self.redo
+    }
+
+    /// Configurations applied during the session, oldest first
+    pub fn applied(&self) -> &[Snapshot] {
+        &self.applied
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A one-output configuration told apart by its position
+    fn at(x: i32) -> Vec<Monitor> {
+        vec![Monitor {
+            x,
+            ..Monitor::with_mode("DP-1", (1920, 1080))
+        }]
+    }
+
+    fn labels(snapshots: &[Snapshot]) -> Vec<&str> {
+        snapshots.iter().map(|s| s.label.as_str()).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_step_through_edits() {
+        let mut history = History::default();
+        history.record("Move DP-1".to_owned(), at(0));
+        history.record("Scale DP-1".to_owned(), at(10));
+
+        assert_eq!(history.undo(at(20)), Some(at(10)));
+        assert_eq!(history.undo(at(10)), Some(at(0)));
+        assert_eq!(history.undo(at(0)), None);
+        assert_eq!(labels(history.undone()), ["Scale DP-1", "Move DP-1"]);
+
+        assert_eq!(history.redo(at(0)), Some(at(10)));
+        assert_eq!(history.redo(at(10)), Some(at(20)));
+        assert_eq!(history.redo(at(20)), None);
+        assert_eq!(labels(history.edits()), ["Move DP-1", "Scale DP-1"]);
+    }
+
+    #[test]
+    fn same_edits_merge_within_the_window() {
+        let start = Instant::now();
+        let mut history = History::default();
+        let scale = || "Scale DP-1".to_owned();
+        history.record_at(scale(), at(0), start);
+        // Each keystroke extends the window
+        history.record_at(scale(), at(1), start + Duration::from_millis(600));
+        history.record_at(scale(), at(2), start + Duration::from_millis(1200));
+        assert_eq!(labels(history.edits()), ["Scale DP-1"]);
+        assert_eq!(history.undo(at(3)), Some(at(0)), "undone as one");
+
+        let mut history = History::default();
+        history.record_at(scale(), at(0), start);
+        history.record_at(scale(), at(1), start + MERGE_WINDOW);
+        assert_eq!(labels(history.edits()), ["Scale DP-1", "Scale DP-1"]);
+
+        let mut history = History::default();
+        history.record_at(scale(), at(0), start);
+        history.record_at("Move DP-1".to_owned(), at(1), start);
+        assert_eq!(
+            labels(history.edits()),
+            ["Scale DP-1", "Move DP-1"],
+            "different edits never merge"
+        );
+    }
+
+    #[test]
+    fn new_edit_drops_redo() {
+        let mut history = History::default();
+        history.record("Move DP-1".to_owned(), at(0));
+        history.undo(at(10));
+        assert!(history.can_redo());
+
+        history.record("Scale DP-1".to_owned(), at(0));
+        assert!(!history.can_redo());
+        assert_eq!(history.redo(at(0)), None);
+    }
+
+    #[test]
+    fn oldest_edits_are_dropped() {
+        let start = Instant::now();
+        let mut history = History::default();
+        for x in 0..=MAX_EDITS as i32 {
+            history.record_at(format!("Move {x}"), at(x), start);
+        }
+        assert_eq!(history.edits().len(), MAX_EDITS);
+        assert_eq!(labels(history.edits()).first(), Some(&"Move 1"));
+    }
+
+    #[test]
+    fn applied_configurations_outlive_edits() {
+        let mut history = History::default();
+        history.record_applied("Session start".to_owned(), at(0));
+        history.record("Move DP-1".to_owned(), at(0));
+        history.record_applied("Applied DP-1".to_owned(), at(10));
+        history.clear_edits();
+        history.record_applied("Roll back to Session start".to_owned(), at(0));
+
+        assert!(!history.can_undo());
+        assert_eq!(
+            labels(history.applied()),
+            [
+                "Session start",
+                "Applied DP-1",
+                "Roll back to Session start"
+            ]
+        );
+        let monitors: Vec<_> = history
+            .applied()
+            .iter()
+            .map(|s| s.monitors.clone())
+            .collect();
+        assert_eq!(monitors, [at(0), at(10), at(0)]);
+    }
+}
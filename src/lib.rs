@@ -1,8 +1,22 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod align;
 mod app;
+mod backend;
+mod canvas;
+mod diagnostics;
+mod diff;
+mod dpi;
+mod edid;
+mod formats;
+mod history;
 mod logic;
 mod models;
+mod physical;
+mod presets;
 mod ui;
+mod validate;
+mod worker;
 
-pub use app::WayDisplay;
+pub use app::{Options, WayDisplay};
+pub use backend::Backend;
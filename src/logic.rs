@@ -1,113 +1,621 @@
-use crate::app::WayDisplay;
-use crate::models::Monitor;
-use std::process::Command;
+use crate::align::{self, Alignment};
+use crate::app::{SwitcherEntry, WayDisplay};
+use crate::backend::{Backend, OutputConfig, command_line};
+use crate::diff::{OutputDiff, diff};
+use crate::dpi;
+use crate::formats::{Import, ImportFormat};
+use crate::models::{Monitor, OutputId, Profile};
+use crate::physical::{self, Align};
+use crate::presets::Preset;
+use crate::validate::validate;
+use crate::worker::{Job, JobResult, Task};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Seconds between two checks for output changes
+const POLL_INTERVAL: f64 = 2.0;
+
+/// Places the outputs in the physical canvas to match their logical positions
+fn place(monitors: &mut [Monitor]) {
+    let positions = physical::physical_positions(monitors);
+    for (m, pos) in monitors.iter_mut().zip(positions) {
+        m.physical_pos = pos;
+    }
+}
+
+/// How the backend should configure `monitor`
+fn output_config(monitor: &Monitor, positioned: bool) -> OutputConfig<'_> {
+    OutputConfig {
+        name: &monitor.name,
+        enabled: monitor.enabled,
+        mode: monitor.current_mode(),
+        position: positioned.then_some((monitor.x, monitor.y)),
+        scale: monitor.scale,
+        transform: monitor.transform,
+        adaptive_sync: monitor.adaptive_sync,
+    }
+}
 
 impl WayDisplay {
+    /// Switches to `backend_override`, falling back to automatic detection
+    pub fn set_backend_override(&mut self, backend_override: Option<Backend>) {
+        self.backend_override = backend_override;
+        self.selected_idx = None;
+        self.selected_mode_idx = None;
+        self.detect_backend(backend_override);
+    }
+
+    /// Probes the system again and leaves the diagnostics screen once a backend is usable
+    pub fn retry_detection(&mut self) {
+        self.detect_backend(self.backend_override);
+    }
+
+    /// Reads the outputs through `backend`, or starts detecting a backend when
+    /// it is `None`, see [`Self::poll_worker`]
+    pub fn detect_backend(&mut self, backend: Option<Backend>) {
+        if let Some(backend) = backend {
+            self.backend = Some(backend);
+            self.diagnostics = None;
+            self.refresh_monitors();
+        } else {
+            self.worker.submit(Job::Detect);
+        }
+    }
+
+    /// Starts probing the system for the diagnostics screen
+    pub fn run_diagnostics(&mut self) {
+        self.worker.submit(Job::Diagnose);
+    }
+
+    /// Starts reading the outputs from the backend, see [`Self::poll_worker`]
     pub fn refresh_monitors(&mut self) {
-        let output = Command::new("wlr-randr").arg("--json").output();
-
-        match output {
-            Ok(out) => {
-                let json_string = String::from_utf8_lossy(&out.stdout);
-                match serde_json::from_str::<Vec<Monitor>>(&json_string) {
-                    Ok(mut data) => {
-                        let monitor_count = data.len();
-                        if monitor_count > 1 {
-                            println!("Multi Monitor: {monitor_count}");
-                            self.is_multi_monitor = true;
-                        } else {
-                            println!("Single Monitor");
-                            self.is_multi_monitor = false;
-                        }
-                        let scale = 80.0 / 1920.0;
-                        for m in &mut data {
-                            m.visual_pos = egui::pos2(m.x as f32 * scale, m.y as f32 * scale);
+        let Some(backend) = self.backend else {
+            self.error_msg = Some("No supported display backend found".to_owned());
+            return;
+        };
+        self.worker.submit(Job::Refresh(backend));
+    }
+
+    /// Checks the backend for added, removed or reconfigured outputs every
+    /// `POLL_INTERVAL` seconds while the window is focused and nothing else is running
+    pub fn watch_outputs(&mut self, ctx: &egui::Context) {
+        let Some(backend) = self.backend.filter(|_| self.live_refresh) else {
+            return;
+        };
+
+        // Nothing is shown while the window is in the background, regaining
+        // focus repaints and polls again
+        let hidden = ctx.input(|i| {
+            let viewport = i.viewport();
+            viewport.focused == Some(false) || viewport.minimized == Some(true)
+        });
+        if hidden {
+            return;
+        }
+
+        let now = ctx.input(|i| i.time);
+        if self.worker.is_idle() && now - self.last_poll >= POLL_INTERVAL {
+            self.last_poll = now;
+            self.worker.submit(Job::Poll(backend));
+        }
+        ctx.request_repaint_after_secs(POLL_INTERVAL as f32);
+    }
+
+    /// Picks up the result of a finished backend call
+    pub fn poll_worker(&mut self) {
+        let Some((task, result)) = self.worker.poll() else {
+            return;
+        };
+        let applying = self.applying.take().filter(|_| task == Task::Apply);
+
+        match result {
+            JobResult::Monitors(Ok(data)) => {
+                // Polling only replaces the list when the outputs actually
+                // changed, so pending edits survive
+                if task == Task::Refresh || data != self.live_monitors {
+                    self.set_monitors(data);
+                }
+                if task == Task::Refresh {
+                    self.error_msg = None;
+                }
+                if self.history.applied().is_empty() && !self.monitors.is_empty() {
+                    self.history
+                        .record_applied("Session start".to_owned(), self.monitors.clone());
+                }
+            }
+            JobResult::Monitors(Err(e)) if task == Task::Poll => {
+                log::debug!("Polling outputs failed: {e}");
+            }
+            JobResult::Applied(Ok(())) => {
+                self.error_msg = None;
+                if let Some((label, outputs)) = applying {
+                    // Outputs that weren't part of the apply kept their live configuration
+                    let mut applied = self.live_monitors.clone();
+                    for m in &mut applied {
+                        if let Some(output) = outputs.iter().find(|o| o.is_same_output(m)) {
+                            *m = output.clone();
                         }
-                        self.monitors = data;
-                        self.error_msg = None;
-                    }
-                    Err(e) => {
-                        self.error_msg = Some(format!("JSON Parse Error: {e}"));
                     }
+                    place(&mut applied);
+                    self.history.record_applied(label, applied);
                 }
+                // Read the outputs back so the pending changes are empty again,
+                // live refresh may be off or paused
+                self.refresh_monitors();
+            }
+            JobResult::Detected(Ok(Some(backend))) => {
+                self.backend = Some(backend);
+                self.diagnostics = None;
+                self.refresh_monitors();
             }
-            Err(e) => {
-                self.error_msg = Some(format!("System Error: {e}"));
+            JobResult::Detected(Ok(None)) => {
+                log::warn!("No supported display backend found");
+                self.backend = None;
+                self.run_diagnostics();
             }
+            JobResult::Diagnosed(Ok(diagnostics)) => {
+                self.diagnostics = Some(diagnostics);
+            }
+            JobResult::Tested(result) => {
+                self.test_result =
+                    Some(result.map(|()| "The compositor accepts this configuration".to_owned()));
+            }
+            JobResult::Monitors(Err(e))
+            | JobResult::Applied(Err(e))
+            | JobResult::Detected(Err(e))
+            | JobResult::Diagnosed(Err(e)) => {
+                self.error_msg = Some(e);
+            }
+            JobResult::Cancelled => {}
         }
     }
 
-    pub fn apply_settings(&mut self) {
-        let (Some(m_idx), Some(mode_idx)) = (self.selected_idx, self.selected_mode_idx) else {
+    /// Replaces the monitor list, keeping the selected output and mode if they still exist
+    fn set_monitors(&mut self, mut data: Vec<Monitor>) {
+        let selected = self.selected_idx.and_then(|i| self.monitors.get(i));
+        let new_idx = selected.and_then(|old| data.iter().position(|m| m.is_same_output(old)));
+
+        let same_outputs = data.len() == self.monitors.len()
+            && data
+                .iter()
+                .zip(&self.monitors)
+                .all(|(a, b)| a.is_same_output(b));
+        if !same_outputs {
+            self.history.clear_edits();
+            self.canvas_selection.clear();
+        }
+
+        let monitor_count = data.len();
+        if monitor_count > 1 {
+            log::info!("Multi Monitor: {monitor_count}");
+            self.is_multi_monitor = true;
+        } else {
+            log::info!("Single Monitor");
+            self.is_multi_monitor = false;
+        }
+
+        self.live_monitors = data.clone();
+        place(&mut data);
+        self.monitors = data;
+        self.apply_aliases();
+        if let Some(idx) = new_idx {
+            self.select_monitor(idx);
+        } else {
+            self.selected_idx = None;
+            self.selected_mode_idx = None;
+        }
+    }
+
+    /// Scales every other output so text appears the same physical size as on output `idx`
+    pub fn match_scales(&mut self, idx: usize) {
+        let Some(reference) = self.monitors.get(idx).cloned() else {
             return;
         };
+        let scales = dpi::matching_scales(&self.monitors, &reference);
+        for (i, (monitor, scale)) in self.monitors.iter_mut().zip(scales).enumerate() {
+            if let Some(scale) = scale.filter(|_| i != idx) {
+                monitor.scale = scale;
+            }
+        }
+    }
 
-        let Some(monitor) = self.monitors.get(m_idx) else {
+    /// Gives output `id` the friendly name `alias`, an empty alias removes it
+    pub fn rename_output(&mut self, id: OutputId, alias: &str) {
+        let alias = alias.trim();
+        if alias.is_empty() {
+            self.aliases.remove(&id);
+        } else {
+            self.aliases.insert(id, alias.to_owned());
+        }
+        self.apply_aliases();
+    }
+
+    /// Fills in `Monitor::alias` from the stored aliases
+    fn apply_aliases(&mut self) {
+        for m in &mut self.monitors {
+            m.alias = self.aliases.get(&m.id()).cloned();
+        }
+    }
+
+    /// Makes `idx` the selected output and loads its settings into the editors
+    pub fn select_monitor(&mut self, idx: usize) {
+        self.selected_idx = Some(idx);
+        let monitor = self.monitors.get(idx);
+        self.selected_mode_idx = monitor.and_then(Monitor::current_mode_idx);
+        if let Some(monitor) = monitor {
+            self.scaling = monitor.scale.to_string();
+        }
+    }
+
+    /// Moves the outputs to the logical positions matching their arrangement
+    /// in the physical canvas
+    pub fn apply_physical_positions(&mut self) {
+        let positions = physical::logical_positions(&self.monitors);
+        for (m, (x, y)) in self.monitors.iter_mut().zip(positions) {
+            m.x = x;
+            m.y = y;
+        }
+    }
+
+    /// Lines the outputs' bezels up side by side and positions them to match
+    pub fn line_up(&mut self, align: Align) {
+        self.history
+            .record(align.label().to_owned(), self.monitors.clone());
+        physical::line_up(&mut self.monitors, align);
+        self.apply_physical_positions();
+    }
+
+    /// Moves output `idx` to a logical position
+    pub fn move_output(&mut self, idx: usize, x: i32, y: i32) {
+        if let Some(m) = self.monitors.get_mut(idx) {
+            m.x = x;
+            m.y = y;
+        }
+        place(&mut self.monitors);
+    }
+
+    /// Moves output `idx` by `dx`, `dy` logical pixels
+    pub fn nudge(&mut self, idx: usize, dx: i32, dy: i32) {
+        let Some(m) = self.monitors.get(idx) else {
             return;
         };
+        let before = self.monitors.clone();
+        let label = format!("Move {}", m.name);
+        self.move_output(idx, m.x + dx, m.y + dy);
+        self.record_edit(label, before);
+    }
+
+    /// Adds output `idx` to the canvas selection or removes it again
+    pub fn toggle_canvas_selection(&mut self, idx: usize) {
+        if self.canvas_selection.is_empty()
+            && let Some(selected) = self.selected_idx.filter(|&i| i != idx)
+        {
+            self.canvas_selection.push(selected);
+        }
+        if let Some(pos) = self.canvas_selection.iter().position(|&i| i == idx) {
+            self.canvas_selection.remove(pos);
+        } else {
+            self.canvas_selection.push(idx);
+        }
+    }
 
-        let Some(mode) = monitor.modes.get(mode_idx) else {
+    /// Applies `alignment` to the outputs selected in the canvas
+    pub fn align_selection(&mut self, alignment: Alignment) {
+        let selection: Vec<usize> = self
+            .canvas_selection
+            .iter()
+            .copied()
+            .filter(|&i| self.monitors.get(i).is_some_and(|m| m.enabled))
+            .collect();
+        let selected: Vec<&Monitor> = selection
+            .iter()
+            .filter_map(|&i| self.monitors.get(i))
+            .collect();
+        let positions = align::align(&selected, alignment);
+        let before = self.monitors.clone();
+        for (&idx, (x, y)) in selection.iter().zip(positions) {
+            self.move_output(idx, x, y);
+        }
+        self.normalize_positions();
+        self.record_edit(alignment.label().to_owned(), before);
+    }
+
+    /// Shifts the enabled outputs so the layout starts at 0,0
+    pub fn normalize_positions(&mut self) {
+        let enabled = || self.monitors.iter().filter(|m| m.enabled);
+        let min_x = enabled().map(|m| m.x).min().unwrap_or(0);
+        let min_y = enabled().map(|m| m.y).min().unwrap_or(0);
+        for m in self.monitors.iter_mut().filter(|m| m.enabled) {
+            m.x -= min_x;
+            m.y -= min_y;
+        }
+        place(&mut self.monitors);
+    }
+
+    /// Enables output `idx`, choosing its preferred mode if none is set
+    pub fn enable_output(&mut self, idx: usize) {
+        let Some(m) = self.monitors.get_mut(idx) else {
             return;
         };
-        let scale = mode.width as f32 / 80.0;
+        m.enabled = true;
+        m.ensure_mode();
+        if self.selected_idx == Some(idx) {
+            self.select_monitor(idx);
+        }
+    }
+
+    /// The pending configuration of all outputs, positioned in logical pixels
+    pub fn layout(&self) -> Vec<Monitor> {
+        self.monitors.clone()
+    }
 
-        // Find normalization point (top-left)
-        let mut min_x = f32::MAX;
-        let mut min_y = f32::MAX;
-        for m in &self.monitors {
-            if m.visual_pos.x < min_x {
-                min_x = m.visual_pos.x;
+    /// Reads the `format` config at `path` into the profile store.
+    ///
+    /// If `path` is a directory every file directly inside it is imported.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the file or directory can't be read.
+    pub fn import_profiles(&mut self, format: ImportFormat, path: &Path) -> Result<Import, String> {
+        let read_error = |e| format!("Reading {} failed: {e}", path.display());
+
+        let files = if path.is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(path)
+                .map_err(read_error)?
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|p| p.is_file())
+                .collect();
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+
+        let mut import = Import::default();
+        for file in &files {
+            let config = fs::read_to_string(file).map_err(read_error)?;
+            let file_name = file
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default();
+            let mut file_import = format.import(&config, &file_name);
+            if files.len() > 1 {
+                for (_, warning) in &mut file_import.warnings {
+                    *warning = format!("{file_name}: {warning}");
+                }
             }
-            if m.visual_pos.y < min_y {
-                min_y = m.visual_pos.y;
+            import.profiles.append(&mut file_import.profiles);
+            import.warnings.append(&mut file_import.warnings);
+        }
+
+        self.profiles.extend(import.profiles.iter().cloned());
+        Ok(import)
+    }
+
+    /// Saves the pending layout as a new profile
+    pub fn save_profile(&mut self) {
+        let name = format!("Profile {}", self.profiles.len() + 1);
+        self.profiles
+            .push(Profile::from_layout(name, &self.layout()));
+    }
+
+    /// Loads the settings of profile `idx` into the matching outputs, to be applied by the user
+    pub fn load_profile(&mut self, idx: usize) {
+        let Some(profile) = self.profiles.get(idx) else {
+            return;
+        };
+        self.history
+            .record(format!("Load {}", profile.name), self.monitors.clone());
+
+        for monitor in &mut self.monitors {
+            let Some(output) = profile.outputs.iter().find(|o| o.matches(monitor)) else {
+                continue;
+            };
+            monitor.enabled = output.enabled;
+            if let Some(mode) = output.mode
+                && let Some(i) = monitor.modes.iter().position(|m| mode.matches(m))
+            {
+                monitor.set_current_mode(i);
+            }
+            if let Some((x, y)) = output.position {
+                monitor.x = x;
+                monitor.y = y;
+            }
+            if let Some(scale) = output.scale {
+                monitor.scale = scale;
+            }
+            if let Some(transform) = output.transform {
+                monitor.transform = transform;
             }
+            if let Some(adaptive_sync) = output.adaptive_sync {
+                monitor.adaptive_sync = adaptive_sync;
+            }
+        }
+        place(&mut self.monitors);
+
+        if let Some(idx) = self.selected_idx {
+            self.select_monitor(idx);
+        }
+    }
+
+    /// Loads the layout `preset` computes for the connected outputs, to be
+    /// confirmed in the alignment canvas and applied by the user
+    pub fn load_preset(&mut self, preset: Preset) {
+        let Some(mut layout) = preset.layout(&self.monitors) else {
+            return;
+        };
+        place(&mut layout);
+        self.history
+            .record(format!("Preset {}", preset.label()), self.monitors.clone());
+        self.restore(layout);
+    }
+
+    /// What the quick switcher offers: profiles whose outputs are all connected,
+    /// then the presets that fit them
+    pub fn switcher_entries(&self) -> Vec<(SwitcherEntry, String)> {
+        let profiles = self
+            .profiles
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.matches(&self.monitors))
+            .map(|(i, p)| (SwitcherEntry::Profile(i), p.name.clone()));
+        let presets = Preset::ALL
+            .into_iter()
+            .filter(|p| p.layout(&self.monitors).is_some())
+            .map(|p| (SwitcherEntry::Preset(p), p.label().to_owned()));
+        profiles.chain(presets).collect()
+    }
+
+    /// Loads `entry` and applies whatever it changes
+    pub fn switch_to(&mut self, entry: SwitcherEntry) {
+        match entry {
+            SwitcherEntry::Profile(i) => self.load_profile(i),
+            SwitcherEntry::Preset(preset) => self.load_preset(preset),
+        }
+        self.error_msg = None;
+        self.apply_changes();
+    }
+
+    /// Records the edit `label` to the pending configuration, `before` is the
+    /// configuration it started from
+    pub fn record_edit(&mut self, label: String, before: Vec<Monitor>) {
+        if before != self.monitors {
+            self.history.record(label, before);
+            self.test_result = None;
         }
+    }
 
-        let px = ((monitor.visual_pos.x - min_x) * scale).round() as i32;
-        let py = ((monitor.visual_pos.y - min_y) * scale).round() as i32;
+    pub fn undo(&mut self) {
+        if let Some(monitors) = self.history.undo(self.monitors.clone()) {
+            self.restore(monitors);
+        }
+    }
 
-        let mut cmd = Command::new("wlr-randr");
-        cmd.arg("--output").arg(&monitor.name);
+    pub fn redo(&mut self) {
+        if let Some(monitors) = self.history.redo(self.monitors.clone()) {
+            self.restore(monitors);
+        }
+    }
 
-        if self.monitor_enabled {
-            cmd.arg("--on");
-            cmd.arg("--custom-mode")
-                .arg(format!("{}x{}@{}", mode.width, mode.height, mode.refresh));
+    /// Makes `monitors` the pending configuration, keeping the selection
+    fn restore(&mut self, monitors: Vec<Monitor>) {
+        self.monitors = monitors;
+        self.apply_aliases();
+        self.test_result = None;
+        match self.selected_idx {
+            Some(idx) if idx < self.monitors.len() => self.select_monitor(idx),
+            _ => {
+                self.selected_idx = None;
+                self.selected_mode_idx = None;
+            }
+        }
+    }
+
+    /// Loads the applied configuration `idx` from the history into the matching
+    /// outputs and applies all of them
+    pub fn roll_back(&mut self, idx: usize) {
+        let Some(snapshot) = self.history.applied().get(idx) else {
+            return;
+        };
+        let label = format!("Roll back to {}", snapshot.label);
 
-            if self.is_multi_monitor {
-                cmd.arg("--pos").arg(format!("{px},{py}"));
+        let mut monitors = self.monitors.clone();
+        for monitor in &mut monitors {
+            if let Some(saved) = snapshot.monitors.iter().find(|m| m.is_same_output(monitor)) {
+                *monitor = saved.clone();
             }
+        }
+        self.history.record(label.clone(), self.monitors.clone());
+        self.restore(monitors);
+        self.apply_layout(label);
+    }
+
+    /// Applies the pending configuration of every output
+    pub fn apply_layout(&mut self, label: String) {
+        let layout = self.layout();
+        self.apply_outputs(label, layout);
+    }
+
+    /// What applying the pending configuration would change, compared with
+    /// the live state from the backend
+    pub fn pending_changes(&self) -> Vec<OutputDiff> {
+        diff(&self.live_monitors, &self.layout())
+    }
 
-            cmd.arg("--scale").arg(self.scaling.clone());
-            cmd.arg("--adaptive-sync").arg(if self.adaptive_sync {
-                "enabled"
-            } else {
-                "disabled"
-            });
+    /// Applies the pending configuration of every output that differs from its live state
+    pub fn apply_changes(&mut self) {
+        let changed = self.pending_changes();
+        let outputs: Vec<Monitor> = self
+            .layout()
+            .into_iter()
+            .filter(|m| changed.iter().any(|d| d.name == m.name))
+            .collect();
+        let label = match outputs.as_slice() {
+            [monitor] => format!("Applied {}", monitor.name),
+            _ => "Applied layout".to_owned(),
+        };
+        self.apply_outputs(label, outputs);
+    }
+
+    /// Checks whether the compositor would accept the pending configuration,
+    /// through the backend where it can test configurations and local rules otherwise
+    pub fn test_layout(&mut self) {
+        let Some(backend) = self.backend else {
+            return;
+        };
+
+        let layout = self.layout();
+        let problems = validate(&layout, self.is_multi_monitor);
+        if !problems.is_empty() {
+            self.test_result = Some(Err(problems.join("\n")));
+            return;
+        }
+
+        let configs: Vec<_> = layout
+            .iter()
+            .map(|m| output_config(m, self.is_multi_monitor))
+            .collect();
+        if let Some(cmd) = backend.test_command(&configs) {
+            self.cmd_output = Some(command_line(&cmd));
+            self.test_result = None;
+            self.worker.submit(Job::Test(cmd));
         } else {
-            cmd.arg("--off");
+            self.test_result = Some(Ok(format!(
+                "Passed local checks, {} can't test configurations itself",
+                backend.label()
+            )));
+        }
+    }
+
+    /// Submits the commands configuring `outputs`, positioned in logical pixels
+    fn apply_outputs(&mut self, label: String, outputs: Vec<Monitor>) {
+        let Some(backend) = self.backend else {
+            return;
+        };
+
+        if self.dry_run {
+            self.test_layout();
+            return;
+        }
+
+        let commands: Vec<_> = outputs
+            .iter()
+            .map(|m| output_config(m, self.is_multi_monitor))
+            .flat_map(|config| backend.apply_commands(&config))
+            .collect();
+        if commands.is_empty() {
+            return;
         }
 
-        let cmd_full = format!(
-            "{} {}",
-            cmd.get_program().to_string_lossy(),
-            cmd.get_args()
-                .map(|a| a.to_string_lossy())
+        self.cmd_output = Some(
+            commands
+                .iter()
+                .map(command_line)
                 .collect::<Vec<_>>()
-                .join(" ")
+                .join("\n"),
         );
-        self.cmd_output = Some(cmd_full);
 
-        match cmd.output() {
-            Ok(output) if output.status.success() => {
-                self.error_msg = None;
-            }
-            Ok(output) => {
-                self.error_msg = Some(String::from_utf8_lossy(&output.stderr).to_string());
-            }
-            Err(e) => {
-                self.error_msg = Some(e.to_string());
-            }
-        }
+        self.applying = Some((label, outputs));
+        self.worker.submit(Job::Apply(commands));
     }
 }
@@ -1,43 +1,89 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 use std::env;
-use std::fs;
-use std::process;
+use way_display::{Backend, Options};
 
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
-    let wlr = "wlr-randr";
+    let options = Options {
+        backend: parse_backend_arg(env::args().skip(1)),
+        dry_run: env::args().any(|arg| arg == "--dry-run"),
+        switcher: env::args().any(|arg| arg == "--switcher"),
+    };
 
-    if check_wlr_randr(wlr) {
-        println!("wlr-randr found!");
+    let viewport = if options.switcher {
+        // Own app id so compositors can float and center it with a window rule
+        egui::ViewportBuilder::default()
+            .with_app_id("waydisplay-switcher")
+            .with_inner_size([320.0, 380.0])
+            .with_decorations(false)
+            .with_resizable(false)
+            .with_always_on_top()
     } else {
-        process::exit(0);
-    }
-
-    let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default(),
+        egui::ViewportBuilder::default()
         // .with_inner_size([400.0, 300.0])
         // .with_min_inner_size([300.0, 220.0]),
+    };
+    let native_options = eframe::NativeOptions {
+        viewport,
+        // Keep the main window's saved size and position
+        persist_window: !options.switcher,
         ..Default::default()
     };
     eframe::run_native(
         "WayDisplay",
         native_options,
-        Box::new(|cc| Ok(Box::new(way_display::WayDisplay::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(way_display::WayDisplay::new(cc, &options)))),
     )
 }
 
-fn check_wlr_randr(program: &str) -> bool {
-    if let Ok(path) = env::var("PATH") {
-        for p in path.split(':') {
-            let p_str = format!("{p}/{program}");
-            if fs::metadata(p_str).is_ok() {
-                return true;
-            }
+/// Reads `--backend <name>` or `--backend=<name>` from the command line
+fn parse_backend_arg(mut args: impl Iterator<Item = String>) -> Option<Backend> {
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--backend") {
+            Some("") => args.next(),
+            Some(rest) => rest.strip_prefix('=').map(str::to_owned),
+            None => continue,
+        };
+        let Some(value) = value else {
+            log::error!("--backend expects one of: wlr-randr, sway, hyprland, niri");
+            return None;
+        };
+        let backend = Backend::from_arg(&value);
+        if backend.is_none() {
+            log::error!("Unknown backend '{value}', falling back to automatic detection");
         }
+        return backend;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Option<Backend> {
+        parse_backend_arg(args.iter().map(|&arg| arg.to_owned()))
+    }
+
+    #[test]
+    fn reads_the_backend_flag() {
+        assert_eq!(parse(&["--backend", "sway"]), Some(Backend::Sway));
+        assert_eq!(
+            parse(&["--dry-run", "--backend=hyprland"]),
+            Some(Backend::Hyprland)
+        );
+        assert_eq!(parse(&["--switcher"]), None, "detected when not given");
+    }
+
+    #[test]
+    fn ignores_unknown_or_missing_backends() {
+        assert_eq!(parse(&["--backend", "kwin"]), None);
+        assert_eq!(parse(&["--backend=kwin"]), None);
+        assert_eq!(parse(&["--backend"]), None);
+        assert_eq!(parse(&["--backends=sway"]), None);
     }
-    false
 }
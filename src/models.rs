@@ -1,6 +1,7 @@
+use crate::edid::Edid;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct Mode {
     pub width: i32,
     pub height: i32,
@@ -9,10 +10,131 @@ pub struct Mode {
     pub current: bool,
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+/// Output rotation and flipping, named like wlr-randr and kanshi do
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transform {
+    #[default]
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "90")]
+    Rotate90,
+    #[serde(rename = "180")]
+    Rotate180,
+    #[serde(rename = "270")]
+    Rotate270,
+    #[serde(rename = "flipped")]
+    Flipped,
+    #[serde(rename = "flipped-90")]
+    Flipped90,
+    #[serde(rename = "flipped-180")]
+    Flipped180,
+    #[serde(rename = "flipped-270")]
+    Flipped270,
+}
+
+impl Transform {
+    pub const ALL: [Self; 8] = [
+        Self::Normal,
+        Self::Rotate90,
+        Self::Rotate180,
+        Self::Rotate270,
+        Self::Flipped,
+        Self::Flipped90,
+        Self::Flipped180,
+        Self::Flipped270,
+    ];
+
+    /// Name used by wlr-randr, sway and kanshi
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Rotate90 => "90",
+            Self::Rotate180 => "180",
+            Self::Rotate270 => "270",
+            Self::Flipped => "flipped",
+            Self::Flipped90 => "flipped-90",
+            Self::Flipped180 => "flipped-180",
+            Self::Flipped270 => "flipped-270",
+        }
+    }
+
+    /// The `wl_output` transform number, which Hyprland uses in its config and IPC
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// Whether width and height swap places
+    pub fn is_rotated(self) -> bool {
+        matches!(
+            self,
+            Self::Rotate90 | Self::Rotate270 | Self::Flipped90 | Self::Flipped270
+        )
+    }
+}
+
+/// What identifies a physical output across ports and docks, from most to least stable
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OutputId {
+    /// `make model serial`, for outputs reporting a serial
+    Description(String),
+    /// Hash of the EDID blob, for outputs without a serial
+    Edid(u64),
+    /// Connector name, for outputs without an EDID
+    Connector(String),
+    /// An identity another connected output has as well, told apart by the connector
+    Shared(Box<Self>, String),
+}
+
+/// Whether `serial` is a stand-in monitors and compositors report in place of
+/// a serial number, like `Unknown` or `0`
+fn is_placeholder_serial(serial: &str) -> bool {
+    let serial = serial.trim();
+    serial.eq_ignore_ascii_case("unknown")
+        || serial.trim_start_matches("0x").chars().all(|c| c == '0')
+}
+
+/// Marks the outputs that would get the same `Monitor::id`, like two identical
+/// monitors without a serial number, so the connector tells them apart.
+///
+/// Outputs without a readable EDID are already identified by connector, but
+/// configs would still name them by the same description, so those count as
+/// shared as well.
+pub fn mark_shared_ids(monitors: &mut [Monitor]) {
+    for m in monitors.iter_mut() {
+        m.shared_id = false;
+    }
+    let ids: Vec<OutputId> = monitors
+        .iter()
+        .map(|m| match m.id() {
+            OutputId::Connector(_) if m.has_identity() => OutputId::Description(m.description()),
+            id => id,
+        })
+        .collect();
+    for (m, id) in monitors.iter_mut().zip(&ids) {
+        m.shared_id = ids.iter().filter(|other| *other == id).count() > 1;
+    }
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct Monitor {
     pub name: String,
+    #[serde(default)]
+    pub make: String,
     pub model: String,
+    #[serde(default)]
+    pub serial: Option<String>,
     pub enabled: bool,
     pub modes: Vec<Mode>,
 
@@ -20,7 +142,338 @@ pub struct Monitor {
     pub x: i32,
     #[serde(default)]
     pub y: i32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub transform: Transform,
+    #[serde(default)]
+    pub adaptive_sync: bool,
 
+    /// Top-left corner of the bezel in millimetres, in the physical canvas
     #[serde(skip)]
-    pub visual_pos: egui::Pos2,
+    pub physical_pos: egui::Pos2,
+    /// Decoded EDID, read from sysfs alongside the backend's output list
+    #[serde(skip)]
+    pub edid: Option<Edid>,
+    /// `Edid::hash` of `edid`, kept when the rest isn't persisted
+    #[serde(default)]
+    pub edid_hash: Option<u64>,
+    /// Another connected output has the same identity, see `mark_shared_ids`
+    #[serde(default)]
+    pub shared_id: bool,
+    /// Friendly name the user gave the output, see `WayDisplay::aliases`
+    #[serde(skip)]
+    pub alias: Option<String>,
+    /// Width and height in millimetres as reported by the backend
+    #[serde(default)]
+    pub physical_size: Option<(u32, u32)>,
+}
+
+impl Monitor {
+    /// Identity of the physical output, independent of the connector where possible
+    pub fn id(&self) -> OutputId {
+        let id = if self.serial_number().is_some() {
+            OutputId::Description(self.description())
+        } else if let Some(hash) = self.edid_hash {
+            OutputId::Edid(hash)
+        } else {
+            return OutputId::Connector(self.name.clone());
+        };
+        if self.shared_id {
+            OutputId::Shared(Box::new(id), self.name.clone())
+        } else {
+            id
+        }
+    }
+
+    /// The serial number, unless the output reports a placeholder
+    pub fn serial_number(&self) -> Option<&str> {
+        self.serial
+            .as_deref()
+            .filter(|serial| !is_placeholder_serial(serial))
+    }
+
+    /// Whether `other` describes the same physical output
+    pub fn is_same_output(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+
+    pub fn current_mode(&self) -> Option<&Mode> {
+        self.modes.iter().find(|m| m.current)
+    }
+
+    pub fn current_mode_idx(&self) -> Option<usize> {
+        self.modes.iter().position(|m| m.current)
+    }
+
+    /// Marks the mode at `idx` as the one to use
+    pub fn set_current_mode(&mut self, idx: usize) {
+        for (i, mode) in self.modes.iter_mut().enumerate() {
+            mode.current = i == idx;
+        }
+    }
+
+    /// Picks the preferred mode, else the first one listed, for outputs that
+    /// are off and have none
+    pub fn ensure_mode(&mut self) {
+        if self.current_mode().is_some() {
+            return;
+        }
+        let idx = self.modes.iter().position(|m| m.preferred);
+        if let Some(idx) = idx.or_else(|| (!self.modes.is_empty()).then_some(0)) {
+            self.set_current_mode(idx);
+        }
+    }
+
+    /// Size in the compositor's layout, after transform and scale
+    pub fn logical_size(&self) -> Option<(f32, f32)> {
+        let mode = self.current_mode()?;
+        let (width, height) = if self.transform.is_rotated() {
+            (mode.height, mode.width)
+        } else {
+            (mode.width, mode.height)
+        };
+        Some((width as f32 / self.scale, height as f32 / self.scale))
+    }
+
+    /// The alias if the user gave the output one, otherwise the connector name
+    pub fn label(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Panel size in millimetres, unrotated, from the backend or else the EDID
+    pub fn physical_size_mm(&self) -> Option<(f32, f32)> {
+        if let Some((w, h)) = self.physical_size.filter(|&(w, h)| w > 0 && h > 0) {
+            return Some((w as f32, h as f32));
+        }
+        let edid = self.edid.as_ref()?;
+        match &edid.native_timing {
+            Some(t) if t.width_mm > 0 && t.height_mm > 0 => {
+                Some((f32::from(t.width_mm), f32::from(t.height_mm)))
+            }
+            _ => edid
+                .size_cm
+                .map(|(w, h)| (f32::from(w) * 10.0, f32::from(h) * 10.0)),
+        }
+    }
+
+    /// Whether the EDID provided any of make, model or serial
+    pub fn has_identity(&self) -> bool {
+        !self.make.is_empty() || !self.model.is_empty() || self.serial_number().is_some()
+    }
+
+    /// `make model serial`, the description compositors and kanshi match outputs by.
+    ///
+    /// Missing parts are written as `Unknown`, like wlroots does.
+    pub fn description(&self) -> String {
+        let part = |s: &str| {
+            if s.is_empty() {
+                "Unknown".to_owned()
+            } else {
+                s.to_owned()
+            }
+        };
+        format!(
+            "{} {} {}",
+            part(&self.make),
+            part(&self.model),
+            part(self.serial.as_deref().unwrap_or_default())
+        )
+    }
+}
+
+#[cfg(test)]
+impl Monitor {
+    /// Enabled output at 0,0 with a single `width`x`height`@60 mode, for tests
+    /// to adjust with struct update syntax
+    pub fn with_mode(name: &str, (width, height): (i32, i32)) -> Self {
+        Self {
+            name: name.to_owned(),
+            make: String::new(),
+            model: String::new(),
+            serial: None,
+            enabled: true,
+            modes: vec![Mode {
+                width,
+                height,
+                refresh: 60.0,
+                preferred: true,
+                current: true,
+            }],
+            x: 0,
+            y: 0,
+            scale: 1.0,
+            transform: Transform::Normal,
+            adaptive_sync: false,
+            physical_pos: egui::Pos2::ZERO,
+            edid: None,
+            edid_hash: None,
+            shared_id: false,
+            alias: None,
+            physical_size: None,
+        }
+    }
+}
+
+/// Mode requested by a profile, any refresh rate matches when `refresh` is unset
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct ProfileMode {
+    pub width: i32,
+    pub height: i32,
+    pub refresh: Option<f32>,
+}
+
+impl ProfileMode {
+    pub fn matches(&self, mode: &Mode) -> bool {
+        self.width == mode.width
+            && self.height == mode.height
+            && self.refresh.is_none_or(|r| (r - mode.refresh).abs() < 0.05)
+    }
+}
+
+/// Settings a profile applies to the outputs matching `criteria`, unset values are left alone
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ProfileOutput {
+    /// Connector name, output description or `*` for any output
+    pub criteria: String,
+    pub enabled: bool,
+    pub mode: Option<ProfileMode>,
+    pub position: Option<(i32, i32)>,
+    pub scale: Option<f32>,
+    pub transform: Option<Transform>,
+    pub adaptive_sync: Option<bool>,
+    /// Primary display of desktops that have one, wlroots compositors don't
+    #[serde(default)]
+    pub primary: bool,
+    /// Identity of the output the settings were saved from, matched instead of
+    /// `criteria` when set
+    #[serde(default)]
+    pub id: Option<OutputId>,
+}
+
+impl ProfileOutput {
+    pub fn matches(&self, monitor: &Monitor) -> bool {
+        if let Some(id) = &self.id {
+            return *id == monitor.id();
+        }
+        self.criteria == "*"
+            || self.criteria == monitor.name
+            || self.criteria == monitor.description()
+    }
+
+    /// Captures the configuration of `monitor`, identified by its output identity
+    pub fn from_monitor(monitor: &Monitor) -> Self {
+        Self {
+            criteria: monitor.description(),
+            enabled: monitor.enabled,
+            mode: monitor.current_mode().map(|m| ProfileMode {
+                width: m.width,
+                height: m.height,
+                refresh: Some(m.refresh),
+            }),
+            position: Some((monitor.x, monitor.y)),
+            scale: Some(monitor.scale),
+            transform: Some(monitor.transform),
+            adaptive_sync: Some(monitor.adaptive_sync),
+            primary: false,
+            id: Some(monitor.id()),
+        }
+    }
+}
+
+/// A saved layout for a set of outputs
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub outputs: Vec<ProfileOutput>,
+    /// Commands to run after the profile was applied
+    #[serde(default)]
+    pub exec: Vec<String>,
+}
+
+impl Profile {
+    pub fn from_layout(name: String, layout: &[Monitor]) -> Self {
+        Self {
+            name,
+            outputs: layout.iter().map(ProfileOutput::from_monitor).collect(),
+            exec: Vec::new(),
+        }
+    }
+
+    /// Whether every output of the profile is connected
+    pub fn matches(&self, monitors: &[Monitor]) -> bool {
+        self.outputs
+            .iter()
+            .all(|o| monitors.iter().any(|m| o.matches(m)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serial_less(name: &str) -> Monitor {
+        Monitor {
+            make: "Dell Inc.".to_owned(),
+            model: "DELL P2419H".to_owned(),
+            serial: Some("Unknown".to_owned()),
+            edid_hash: Some(0x5eed),
+            ..Monitor::with_mode(name, (1920, 1080))
+        }
+    }
+
+    #[test]
+    fn placeholder_serials_are_no_serial() {
+        for serial in ["", "Unknown", "0", "0x00000000"] {
+            let monitor = Monitor {
+                serial: Some(serial.to_owned()),
+                ..serial_less("DP-1")
+            };
+            assert_eq!(monitor.id(), OutputId::Edid(0x5eed), "serial {serial:?}");
+        }
+        let monitor = Monitor {
+            serial: Some("8RZ1XC3".to_owned()),
+            ..serial_less("DP-1")
+        };
+        assert_eq!(
+            monitor.id(),
+            OutputId::Description("Dell Inc. DELL P2419H 8RZ1XC3".to_owned())
+        );
+    }
+
+    #[test]
+    fn identical_outputs_are_told_apart_by_connector() {
+        let mut monitors = [serial_less("DP-1"), serial_less("DP-2")];
+        mark_shared_ids(&mut monitors);
+        let [left, right] = &monitors;
+        assert!(!left.is_same_output(right));
+        assert_eq!(
+            right.id(),
+            OutputId::Shared(Box::new(OutputId::Edid(0x5eed)), "DP-2".to_owned())
+        );
+
+        // Alone, the output is recognised on any connector again
+        let mut monitors = [serial_less("HDMI-A-1")];
+        mark_shared_ids(&mut monitors);
+        let [moved] = &monitors;
+        assert_eq!(moved.id(), OutputId::Edid(0x5eed));
+    }
+
+    #[test]
+    fn identical_outputs_without_edid_are_shared() {
+        let no_edid = |name| Monitor {
+            edid_hash: None,
+            ..serial_less(name)
+        };
+        let mut monitors = [no_edid("DP-1"), no_edid("DP-2")];
+        mark_shared_ids(&mut monitors);
+        let [left, right] = &monitors;
+        assert!(left.shared_id && right.shared_id, "same description");
+        assert_eq!(right.id(), OutputId::Connector("DP-2".to_owned()));
+
+        let mut monitors = [no_edid("DP-1")];
+        mark_shared_ids(&mut monitors);
+        let [alone] = &monitors;
+        assert!(!alone.shared_id);
+    }
 }
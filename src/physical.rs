@@ -0,0 +1,329 @@
+use crate::dpi::DEFAULT_TARGET_DPI;
+use crate::models::Monitor;
+
+/// Millimetres per logical pixel assumed when placing outputs in the physical
+/// canvas from their logical position
+pub const MM_PER_PX: f32 = 25.4 / DEFAULT_TARGET_DPI;
+
+/// Edge the outputs' bezels are lined up by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl Align {
+    pub const ALL: [Self; 3] = [Self::Top, Self::Center, Self::Bottom];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Top => "Line Up Tops",
+            Self::Center => "Line Up Centres",
+            Self::Bottom => "Line Up Bottoms",
+        }
+    }
+}
+
+/// Size of `monitor` in millimetres as it stands on the desk, after rotation.
+///
+/// Without a known physical size the current mode is assumed to be 96 PPI.
+pub fn size_mm(monitor: &Monitor) -> Option<(f32, f32)> {
+    let (width, height) = monitor.physical_size_mm().or_else(|| {
+        let mode = monitor.current_mode()?;
+        Some((
+            mode.width as f32 * MM_PER_PX,
+            mode.height as f32 * MM_PER_PX,
+        ))
+    })?;
+    Some(if monitor.transform.is_rotated() {
+        (height, width)
+    } else {
+        (width, height)
+    })
+}
+
+/// Bezel rectangle of `monitor` in millimetres, `None` without a mode
+fn rect(monitor: &Monitor) -> Option<egui::Rect> {
+    let (width, height) = size_mm(monitor)?;
+    Some(egui::Rect::from_min_size(
+        monitor.physical_pos,
+        egui::vec2(width, height),
+    ))
+}
+
+/// Places the outputs side by side from left to right in their current
+/// order, with their bezels lined up by `align`
+pub fn line_up(monitors: &mut [Monitor], align: Align) {
+    let mut outputs: Vec<(egui::Rect, &mut Monitor)> = monitors
+        .iter_mut()
+        .filter(|m| m.enabled)
+        .filter_map(|m| Some((rect(m)?, m)))
+        .collect();
+    outputs.sort_by(|(a, _), (b, _)| a.left().total_cmp(&b.left()));
+    let Some(&(first, _)) = outputs.first() else {
+        return;
+    };
+
+    let mut x = first.left();
+    for (r, monitor) in outputs {
+        let y = match align {
+            Align::Top => first.top(),
+            Align::Center => first.center().y - r.height() / 2.0,
+            Align::Bottom => first.bottom() - r.height(),
+        };
+        monitor.physical_pos = egui::pos2(x, y);
+        x += r.width();
+    }
+}
+
+/// Logical positions that keep the physical arrangement of the outputs, so
+/// the cursor leaves one output and enters its neighbour at the same height
+/// on the desk, see [`join`].
+///
+/// Disabled outputs and outputs without a mode keep their position.
+pub fn logical_positions(monitors: &[Monitor]) -> Vec<(i32, i32)> {
+    // Bezel in mm and logical pixels per mm of every output with a mode
+    let outputs: Vec<Option<(egui::Rect, egui::Vec2)>> = monitors
+        .iter()
+        .map(|m| {
+            if !m.enabled {
+                return None;
+            }
+            let r = rect(m)?;
+            let (width, height) = m.logical_size()?;
+            Some((r, egui::vec2(width / r.width(), height / r.height())))
+        })
+        .collect();
+
+    let positions = join(&outputs);
+
+    let min = positions
+        .iter()
+        .flatten()
+        .fold(egui::pos2(f32::MAX, f32::MAX), |min, p| min.min(*p));
+    monitors
+        .iter()
+        .zip(positions)
+        .map(|(m, pos)| {
+            pos.map_or((m.x, m.y), |pos| {
+                (
+                    (pos.x - min.x).round() as i32,
+                    (pos.y - min.y).round() as i32,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Places in the physical canvas matching the logical positions of the
+/// outputs, the inverse of [`logical_positions`]: neighbours' bezels touch
+/// where their logical edges do, whatever their pixel density.
+///
+/// Disabled outputs and outputs without a mode are placed at 96 PPI.
+pub fn physical_positions(monitors: &[Monitor]) -> Vec<egui::Pos2> {
+    // Logical rectangle and mm per logical pixel of every output with a mode
+    let outputs: Vec<Option<(egui::Rect, egui::Vec2)>> = monitors
+        .iter()
+        .map(|m| {
+            if !m.enabled {
+                return None;
+            }
+            let (width, height) = m.logical_size()?;
+            let (width_mm, height_mm) = size_mm(m)?;
+            let r = egui::Rect::from_min_size(
+                egui::pos2(m.x as f32, m.y as f32),
+                egui::vec2(width, height),
+            );
+            Some((r, egui::vec2(width_mm / width, height_mm / height)))
+        })
+        .collect();
+
+    join(&outputs)
+        .into_iter()
+        .zip(monitors)
+        .map(|(pos, m)| pos.unwrap_or_else(|| egui::pos2(m.x as f32, m.y as f32) * MM_PER_PX))
+        .collect()
+}
+
+/// Joins the outputs one at a time to their closest already placed neighbour,
+/// going from the space of their rectangles to the space `scale` converts to.
+///
+/// Where two neighbours differ in scale, the middle of their shared edge is
+/// what lines up exactly. The top-left output keeps its position, converted
+/// by its own scale. Outputs that are `None` are left unplaced.
+fn join(outputs: &[Option<(egui::Rect, egui::Vec2)>]) -> Vec<Option<egui::Pos2>> {
+    let mut positions: Vec<Option<egui::Pos2>> = vec![None; outputs.len()];
+    let anchor = outputs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, o)| Some((i, o.as_ref()?.0.min)))
+        .min_by(|(_, a), (_, b)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    if let Some((i, min)) = anchor
+        && let (Some(pos), Some(Some((_, scale)))) = (positions.get_mut(i), outputs.get(i))
+    {
+        *pos = Some((min.to_vec2() * *scale).to_pos2());
+    }
+
+    loop {
+        // Closest pair of an unplaced output `u` and a placed output `p`
+        let mut closest = None;
+        let mut closest_gap = f32::MAX;
+        for (u, (output, pos)) in outputs.iter().zip(&positions).enumerate() {
+            let Some((ru, scale_u)) = output.filter(|_| pos.is_none()) else {
+                continue;
+            };
+            for (output, pos) in outputs.iter().zip(&positions) {
+                let (Some((rp, scale_p)), Some(pos_p)) = (output, pos) else {
+                    continue;
+                };
+                let gap = gaps(ru, *rp).max_elem();
+                if gap < closest_gap {
+                    closest_gap = gap;
+                    closest = Some((u, ru, scale_u, *rp, *scale_p, *pos_p));
+                }
+            }
+        }
+        let Some((u, ru, scale_u, rp, scale_p, pos_p)) = closest else {
+            break;
+        };
+        let size_u = ru.size() * scale_u;
+        let size_p = rp.size() * scale_p;
+
+        let gap = gaps(ru, rp);
+        let pos = if gap.x >= gap.y {
+            // Side by side, match the height of the middle of the shared edge
+            let x = if ru.center().x > rp.center().x {
+                pos_p.x + size_p.x
+            } else {
+                pos_p.x - size_u.x
+            };
+            let mid = f32::midpoint(ru.top().max(rp.top()), ru.bottom().min(rp.bottom()));
+            let y = pos_p.y + (mid - rp.top()) * scale_p.y - (mid - ru.top()) * scale_u.y;
+            egui::pos2(x, y)
+        } else {
+            // Stacked, match the middle of the shared edge horizontally
+            let y = if ru.center().y > rp.center().y {
+                pos_p.y + size_p.y
+            } else {
+                pos_p.y - size_u.y
+            };
+            let mid = f32::midpoint(ru.left().max(rp.left()), ru.right().min(rp.right()));
+            let x = pos_p.x + (mid - rp.left()) * scale_p.x - (mid - ru.left()) * scale_u.x;
+            egui::pos2(x, y)
+        };
+        if let Some(slot) = positions.get_mut(u) {
+            *slot = Some(pos);
+        }
+    }
+    positions
+}
+
+/// Horizontal and vertical distance between `a` and `b`, negative where they overlap
+fn gaps(a: egui::Rect, b: egui::Rect) -> egui::Vec2 {
+    egui::vec2(
+        (a.left() - b.right()).max(b.left() - a.right()),
+        (a.top() - b.bottom()).max(b.top() - a.bottom()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(
+        name: &str,
+        mode: (i32, i32),
+        scale: f32,
+        mm: (u32, u32),
+        pos: (f32, f32),
+    ) -> Monitor {
+        Monitor {
+            scale,
+            physical_size: Some(mm),
+            physical_pos: egui::pos2(pos.0, pos.1),
+            ..Monitor::with_mode(name, mode)
+        }
+    }
+
+    #[test]
+    fn same_density_keeps_proportions() {
+        let mut monitors = [
+            monitor("DP-1", (2560, 1440), 1.0, (640, 360), (0.0, 0.0)),
+            monitor("DP-2", (1920, 1080), 1.0, (480, 270), (900.0, 0.0)),
+        ];
+        line_up(&mut monitors, Align::Center);
+        let [_, external] = &monitors;
+        assert_eq!(
+            external.physical_pos,
+            egui::pos2(640.0, 45.0),
+            "placed next to DP-1"
+        );
+
+        assert_eq!(
+            logical_positions(&monitors),
+            [(0, 0), (2560, 180)],
+            "centres line up"
+        );
+    }
+
+    #[test]
+    fn mixed_density_lines_up_centres() {
+        // 5 and 4 logical pixels per mm
+        let mut monitors = [
+            monitor("eDP-1", (2880, 1800), 2.0, (288, 180), (-400.0, 0.0)),
+            monitor("DP-1", (3840, 2160), 1.5, (640, 360), (0.0, 0.0)),
+        ];
+        line_up(&mut monitors, Align::Center);
+
+        // Centres at 270 + 900 / 2 and 1440 / 2
+        assert_eq!(logical_positions(&monitors), [(0, 270), (1440, 0)]);
+    }
+
+    #[test]
+    fn stacked_outputs_line_up_horizontally() {
+        // 4 and 6 logical pixels per mm, the laptop centred below
+        let monitors = [
+            monitor("DP-1", (2560, 1440), 1.0, (640, 360), (0.0, 0.0)),
+            monitor("eDP-1", (1920, 1200), 1.0, (320, 200), (160.0, 360.0)),
+        ];
+
+        // Centres at 1280 and 320 + 1920 / 2
+        assert_eq!(logical_positions(&monitors), [(0, 0), (320, 1440)]);
+    }
+
+    #[test]
+    fn physical_positions_join_bezels() {
+        // 4 and 5 logical pixels per mm, the laptop panel right of the centre
+        let monitors = [
+            Monitor {
+                physical_size: Some((640, 360)),
+                ..Monitor::with_mode("DP-1", (2560, 1440))
+            },
+            Monitor {
+                x: 2560,
+                y: 270,
+                scale: 2.0,
+                physical_size: Some((288, 180)),
+                ..Monitor::with_mode("eDP-1", (2880, 1800))
+            },
+        ];
+        let positions = physical_positions(&monitors);
+        let [dp, edp] = positions.as_slice() else {
+            panic!("one position per output");
+        };
+        assert_eq!(*dp, egui::Pos2::ZERO);
+        // Touching the right bezel, centres at the same height
+        assert!(
+            (*edp - egui::pos2(640.0, 90.0)).length() < 0.01,
+            "got {edp:?}"
+        );
+
+        let placed: Vec<Monitor> = monitors
+            .into_iter()
+            .zip(positions)
+            .map(|(m, physical_pos)| Monitor { physical_pos, ..m })
+            .collect();
+        assert_eq!(logical_positions(&placed), [(0, 0), (2560, 270)]);
+    }
+}
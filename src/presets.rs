@@ -0,0 +1,334 @@
+use crate::models::Monitor;
+
+/// Connector prefixes of built-in laptop panels
+const INTERNAL_CONNECTORS: [&str; 2] = ["eDP-", "LVDS-"];
+
+/// One-click layouts computed from the connected outputs, like the Windows projection menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    InternalOnly,
+    ExternalOnly,
+    ExtendRight,
+    ExtendLeft,
+    ExtendAbove,
+    ExtendBelow,
+    Mirror,
+}
+
+/// Where the extended outputs go, relative to the main one
+#[derive(Clone, Copy)]
+enum Side {
+    Right,
+    Left,
+    Above,
+    Below,
+}
+
+impl Preset {
+    pub const ALL: [Self; 7] = [
+        Self::InternalOnly,
+        Self::ExternalOnly,
+        Self::ExtendRight,
+        Self::ExtendLeft,
+        Self::ExtendAbove,
+        Self::ExtendBelow,
+        Self::Mirror,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::InternalOnly => "Internal Only",
+            Self::ExternalOnly => "External Only",
+            Self::ExtendRight => "Extend Right",
+            Self::ExtendLeft => "Extend Left",
+            Self::ExtendAbove => "Extend Above",
+            Self::ExtendBelow => "Extend Below",
+            Self::Mirror => "Mirror",
+        }
+    }
+
+    /// What the connected outputs lack when [`Self::layout`] gives `None`
+    pub fn requirement(self) -> &'static str {
+        match self {
+            Self::InternalOnly | Self::ExternalOnly => {
+                "Needs a laptop panel and an external display"
+            }
+            Self::ExtendRight
+            | Self::ExtendLeft
+            | Self::ExtendAbove
+            | Self::ExtendBelow
+            | Self::Mirror => "Needs at least two displays",
+        }
+    }
+
+    /// The full layout this preset gives `monitors`, positioned in logical pixels.
+    ///
+    /// `None` if the preset doesn't fit the connected outputs, e.g. there is
+    /// no laptop panel to keep on or fewer than two outputs.
+    pub fn layout(self, monitors: &[Monitor]) -> Option<Vec<Monitor>> {
+        if monitors.len() < 2 {
+            return None;
+        }
+        let has_internal = monitors.iter().any(is_internal);
+        let has_external = monitors.iter().any(|m| !is_internal(m));
+
+        let mut layout = monitors.to_vec();
+        for monitor in &mut layout {
+            monitor.ensure_mode();
+        }
+        match self {
+            Self::InternalOnly | Self::ExternalOnly => {
+                if !has_internal || !has_external {
+                    return None;
+                }
+                let internal = self == Self::InternalOnly;
+                for monitor in &mut layout {
+                    monitor.enabled = is_internal(monitor) == internal;
+                }
+                let enabled: Vec<&mut Monitor> = layout.iter_mut().filter(|m| m.enabled).collect();
+                arrange(enabled, Side::Right);
+            }
+            Self::ExtendRight | Self::ExtendLeft | Self::ExtendAbove | Self::ExtendBelow => {
+                let side = match self {
+                    Self::ExtendLeft => Side::Left,
+                    Self::ExtendAbove => Side::Above,
+                    Self::ExtendBelow => Side::Below,
+                    _ => Side::Right,
+                };
+                // The laptop panel stays put, everything else is added on `side`
+                let main = layout.iter().position(is_internal).unwrap_or_default();
+                for monitor in &mut layout {
+                    monitor.enabled = true;
+                }
+                let mut ordered: Vec<&mut Monitor> = layout.iter_mut().collect();
+                let main = ordered.remove(main);
+                ordered.insert(0, main);
+                arrange(ordered, side);
+            }
+            Self::Mirror => mirror(&mut layout),
+        }
+        normalize(&mut layout);
+        Some(layout)
+    }
+}
+
+/// Whether `monitor` is a built-in laptop panel, going by its connector name
+pub fn is_internal(monitor: &Monitor) -> bool {
+    INTERNAL_CONNECTORS
+        .iter()
+        .any(|prefix| monitor.name.starts_with(prefix))
+}
+
+/// Places the first output at the origin and chains the others on its `side`,
+/// edges touching and tops (or lefts) aligned
+fn arrange(monitors: Vec<&mut Monitor>, side: Side) {
+    let (mut x, mut y) = (0, 0);
+    let mut first = true;
+    for monitor in monitors {
+        let (width, height) = monitor.logical_size().unwrap_or((1920.0, 1080.0));
+        let (width, height) = (width.round() as i32, height.round() as i32);
+        if !first {
+            match side {
+                Side::Left => x -= width,
+                Side::Above => y -= height,
+                Side::Right | Side::Below => {}
+            }
+        }
+        monitor.x = x;
+        monitor.y = y;
+        match side {
+            Side::Right => x += width,
+            Side::Below => y += height,
+            Side::Left | Side::Above => {}
+        }
+        first = false;
+    }
+}
+
+/// Puts every output at the origin, using the largest resolution all of them support
+fn mirror(layout: &mut [Monitor]) {
+    let Some((first, rest)) = layout.split_first() else {
+        return;
+    };
+    let mut sizes: Vec<(i32, i32)> = first.modes.iter().map(|m| (m.width, m.height)).collect();
+    sizes.sort_by_key(|(w, h)| std::cmp::Reverse(w * h));
+    let common = sizes.into_iter().find(|&(w, h)| {
+        rest.iter().all(|m| {
+            m.modes
+                .iter()
+                .any(|mode| mode.width == w && mode.height == h)
+        })
+    });
+    let scale = first.scale;
+
+    for monitor in layout {
+        monitor.enabled = true;
+        monitor.x = 0;
+        monitor.y = 0;
+        monitor.scale = scale;
+        if let Some((w, h)) = common {
+            let best = monitor
+                .modes
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.width == w && m.height == h)
+                .max_by(|(_, a), (_, b)| a.refresh.total_cmp(&b.refresh))
+                .map(|(i, _)| i);
+            if let Some(i) = best {
+                monitor.set_current_mode(i);
+            }
+        }
+    }
+}
+
+/// Moves the enabled outputs so their top-left corner is the origin
+fn normalize(layout: &mut [Monitor]) {
+    let enabled = layout.iter().filter(|m| m.enabled);
+    let min_x = enabled.clone().map(|m| m.x).min().unwrap_or_default();
+    let min_y = enabled.map(|m| m.y).min().unwrap_or_default();
+    for monitor in layout.iter_mut().filter(|m| m.enabled) {
+        monitor.x -= min_x;
+        monitor.y -= min_y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Mode;
+
+    fn mode((width, height): (i32, i32), refresh: f32) -> Mode {
+        Mode {
+            width,
+            height,
+            refresh,
+            preferred: false,
+            current: false,
+        }
+    }
+
+    /// Whether each output is on and where, in the order passed to the preset
+    fn positions(layout: &[Monitor]) -> Vec<(bool, i32, i32)> {
+        layout.iter().map(|m| (m.enabled, m.x, m.y)).collect()
+    }
+
+    fn laptop_and_external() -> [Monitor; 2] {
+        [
+            Monitor::with_mode("eDP-1", (1920, 1200)),
+            Monitor {
+                x: 1920,
+                ..Monitor::with_mode("DP-1", (2560, 1440))
+            },
+        ]
+    }
+
+    #[test]
+    fn internal_or_external_only() {
+        let monitors = laptop_and_external();
+        let layout = Preset::InternalOnly.layout(&monitors);
+        assert_eq!(
+            layout.as_deref().map(positions),
+            Some(vec![(true, 0, 0), (false, 1920, 0)])
+        );
+        let layout = Preset::ExternalOnly.layout(&monitors);
+        assert_eq!(
+            layout.as_deref().map(positions),
+            Some(vec![(false, 0, 0), (true, 0, 0)])
+        );
+    }
+
+    #[test]
+    fn presets_need_fitting_outputs() {
+        let monitors = [
+            Monitor::with_mode("DP-1", (2560, 1440)),
+            Monitor::with_mode("DP-2", (1920, 1080)),
+        ];
+        assert!(Preset::InternalOnly.layout(&monitors).is_none());
+        assert!(Preset::ExternalOnly.layout(&monitors).is_none());
+        assert!(Preset::ExtendRight.layout(&monitors).is_some());
+
+        let [laptop, _] = laptop_and_external();
+        assert!(Preset::Mirror.layout(&[laptop]).is_none());
+    }
+
+    #[test]
+    fn extend_keeps_the_laptop_as_main() {
+        // Listed after the external display, still the one the others are placed around
+        let [laptop, external] = laptop_and_external();
+        let monitors = [external, laptop];
+        let placed = |preset: Preset| preset.layout(&monitors).as_deref().map(positions);
+
+        assert_eq!(
+            placed(Preset::ExtendRight),
+            Some(vec![(true, 1920, 0), (true, 0, 0)])
+        );
+        assert_eq!(
+            placed(Preset::ExtendLeft),
+            Some(vec![(true, 0, 0), (true, 2560, 0)])
+        );
+        assert_eq!(
+            placed(Preset::ExtendAbove),
+            Some(vec![(true, 0, 0), (true, 0, 1440)])
+        );
+        assert_eq!(
+            placed(Preset::ExtendBelow),
+            Some(vec![(true, 0, 1200), (true, 0, 0)])
+        );
+    }
+
+    #[test]
+    fn mirror_uses_the_largest_common_mode() {
+        let mut laptop = Monitor {
+            scale: 1.5,
+            modes: vec![mode((1920, 1200), 60.0), mode((1920, 1080), 60.0)],
+            ..Monitor::with_mode("eDP-1", (0, 0))
+        };
+        laptop.set_current_mode(0);
+        let mut external = Monitor {
+            x: 1280,
+            modes: vec![
+                mode((2560, 1440), 60.0),
+                mode((1920, 1080), 60.0),
+                mode((1920, 1080), 144.0),
+            ],
+            ..Monitor::with_mode("DP-1", (0, 0))
+        };
+        external.set_current_mode(0);
+
+        let layout = Preset::Mirror
+            .layout(&[laptop, external])
+            .unwrap_or_default();
+        let modes: Vec<_> = layout
+            .iter()
+            .map(|m| (m.x, m.y, m.scale, m.current_mode_idx()))
+            .collect();
+        // The fastest refresh rate of that size
+        assert_eq!(modes, [(0, 0, 1.5, Some(1)), (0, 0, 1.5, Some(2))]);
+    }
+
+    #[test]
+    fn enabled_outputs_get_a_mode() {
+        let [laptop, external] = laptop_and_external();
+        let external = Monitor {
+            enabled: false,
+            modes: vec![
+                mode((1920, 1080), 60.0),
+                Mode {
+                    preferred: true,
+                    ..mode((2560, 1440), 60.0)
+                },
+            ],
+            ..external
+        };
+
+        let layout = Preset::ExtendRight
+            .layout(&[laptop, external])
+            .unwrap_or_default();
+        assert_eq!(positions(&layout), [(true, 0, 0), (true, 1920, 0)]);
+        assert_eq!(
+            layout.get(1).and_then(Monitor::current_mode_idx),
+            Some(1),
+            "preferred mode"
+        );
+    }
+}
@@ -1,5 +1,19 @@
-use crate::app::WayDisplay;
-use egui::{Align, Context, Layout, RichText, Ui, vec2};
+use crate::app::{ExportDialog, ImportDialog, WayDisplay};
+use crate::backend::Backend;
+use crate::diagnostics::{Probe, ProbeStatus};
+use crate::dpi;
+use crate::edid::Edid;
+use crate::formats::{self, ExportFormat, ImportFormat};
+use crate::models::Transform;
+use crate::physical;
+use crate::presets::Preset;
+use crate::worker::Task;
+use egui::{Align, Context, Key, KeyboardShortcut, Layout, Modifiers, RichText, Ui, vec2};
+use std::time::Duration;
+
+const UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
 
 impl WayDisplay {
     pub fn apply_style(&self, ctx: &Context) {
@@ -16,25 +30,207 @@ impl WayDisplay {
         ctx.set_fonts(fonts);
     }
 
-    pub fn render_top_panel(&self, ctx: &Context) {
+    /// Undo and redo, unless a text field has focus and wants them for itself
+    pub fn handle_shortcuts(&mut self, ctx: &Context) {
+        // Text fields handle their own keys, focused buttons and sliders don't
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        // Redo first, Ctrl+Z would also match Ctrl+Shift+Z
+        if ctx.input_mut(|i| i.consume_shortcut(&REDO)) {
+            self.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&UNDO)) {
+            self.undo();
+        }
+
+        // Disabled outputs sit in the tray, outside the layout
+        let nudgeable = self
+            .selected_idx
+            .filter(|&idx| self.monitors.get(idx).is_some_and(|m| m.enabled));
+        if let Some(idx) = nudgeable.filter(|_| self.is_multi_monitor) {
+            let (dx, dy) = ctx.input_mut(|i| {
+                let mut nudge = |key, x, y| {
+                    if i.consume_key(Modifiers::SHIFT, key) {
+                        (x * 10, y * 10)
+                    } else if i.consume_key(Modifiers::NONE, key) {
+                        (x, y)
+                    } else {
+                        (0, 0)
+                    }
+                };
+                let moves = [
+                    nudge(Key::ArrowLeft, -1, 0),
+                    nudge(Key::ArrowRight, 1, 0),
+                    nudge(Key::ArrowUp, 0, -1),
+                    nudge(Key::ArrowDown, 0, 1),
+                ];
+                moves
+                    .iter()
+                    .fold((0, 0), |(x, y), (dx, dy)| (x + dx, y + dy))
+            });
+            if (dx, dy) != (0, 0) {
+                self.nudge(idx, dx, dy);
+            }
+        }
+    }
+
+    pub fn render_top_panel(&mut self, ctx: &Context) {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.add_space(2.0);
+            if self.dry_run {
+                ui.label(
+                    RichText::new(format!(
+                        "{}  Dry run: Apply only tests configurations, nothing is changed",
+                        egui_phosphor::regular::FLASK
+                    ))
+                    .color(ui.visuals().warn_fg_color),
+                );
+            }
             egui::MenuBar::new().ui(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    ui.add_enabled_ui(!self.monitors.is_empty(), |ui| {
+                        ui.menu_button("Export Layout", |ui| {
+                            for format in ExportFormat::ALL {
+                                if ui.button(format.label()).clicked() {
+                                    self.export = Some(ExportDialog::new(format));
+                                }
+                            }
+                        });
+                    });
+                    ui.menu_button("Import Layouts", |ui| {
+                        for format in ImportFormat::ALL {
+                            if ui.button(format.label()).clicked() {
+                                self.import = Some(ImportDialog::new(format));
+                            }
+                        }
+                    });
+                    ui.separator();
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
                 });
+                ui.menu_button("Profiles", |ui| {
+                    let mut load = None;
+                    let mut delete = None;
+                    for (i, profile) in self.profiles.iter().enumerate() {
+                        let connected = profile.matches(&self.monitors);
+                        ui.menu_button(&profile.name, |ui| {
+                            if ui
+                                .add_enabled(connected, egui::Button::new("Load"))
+                                .on_disabled_hover_text("Not all outputs are connected")
+                                .clicked()
+                            {
+                                load = Some(i);
+                            }
+                            if ui.button("Delete").clicked() {
+                                delete = Some(i);
+                            }
+                            for output in &profile.outputs {
+                                let label = if output.primary {
+                                    format!("{} (primary)", output.criteria)
+                                } else {
+                                    output.criteria.clone()
+                                };
+                                ui.label(RichText::new(label).weak());
+                            }
+                            for exec in &profile.exec {
+                                ui.label(RichText::new(format!("exec {exec}")).code());
+                            }
+                        });
+                    }
+                    if !self.profiles.is_empty() {
+                        ui.separator();
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.monitors.is_empty(),
+                            egui::Button::new("Save Current Layout"),
+                        )
+                        .clicked()
+                    {
+                        self.save_profile();
+                    }
+                    if let Some(i) = load {
+                        self.load_profile(i);
+                        ui.close();
+                    }
+                    if let Some(i) = delete {
+                        self.profiles.remove(i);
+                    }
+                });
                 ui.menu_button("Edit", |ui| {
+                    if ui
+                        .add_enabled(
+                            self.history.can_undo(),
+                            egui::Button::new("Undo").shortcut_text(ctx.format_shortcut(&UNDO)),
+                        )
+                        .clicked()
+                    {
+                        self.undo();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.history.can_redo(),
+                            egui::Button::new("Redo").shortcut_text(ctx.format_shortcut(&REDO)),
+                        )
+                        .clicked()
+                    {
+                        self.redo();
+                    }
+                    if ui.button("History").clicked() {
+                        self.show_history = true;
+                        ui.close();
+                    }
+                    ui.separator();
                     ui.menu_button("Theme", |ui| {
                         egui::widgets::global_theme_preference_buttons(ui);
                     });
+                    let backend_menu = ui.menu_button("Backend", |ui| {
+                        // Scanning PATH every frame is wasteful, look once per opening
+                        let available = self
+                            .available_backends
+                            .get_or_insert_with(|| {
+                                Backend::ALL
+                                    .into_iter()
+                                    .filter(|b| b.is_available())
+                                    .collect()
+                            })
+                            .clone();
+                        let mut choice = self.backend_override;
+                        let auto_label = match self.backend {
+                            Some(backend) if self.backend_override.is_none() => {
+                                format!("Automatic ({})", backend.label())
+                            }
+                            _ => "Automatic".to_owned(),
+                        };
+                        ui.radio_value(&mut choice, None, auto_label);
+                        for backend in Backend::ALL {
+                            ui.add_enabled_ui(available.contains(&backend), |ui| {
+                                ui.radio_value(&mut choice, Some(backend), backend.label())
+                                    .on_disabled_hover_text(format!(
+                                        "{} not found in PATH",
+                                        backend.program()
+                                    ));
+                            });
+                        }
+                        if choice != self.backend_override {
+                            self.set_backend_override(choice);
+                            ui.close();
+                        }
+                    });
+                    if backend_menu.inner.is_none() {
+                        self.available_backends = None;
+                    }
+                    ui.checkbox(&mut self.live_refresh, "Live Refresh");
+                    if ui.button("Diagnostics").clicked() {
+                        self.run_diagnostics();
+                    }
                 });
             });
         });
     }
 
-    pub fn render_bottom_panel(&self, ctx: &Context) {
+    pub fn render_bottom_panel(&mut self, ctx: &Context) {
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             ui.add_space(7.0);
             ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
@@ -45,11 +241,466 @@ impl WayDisplay {
                     ),
                     "https://github.com/RoccoRakete/WayDisplay",
                 );
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if let Some(task) = self.worker.busy_task() {
+                        // Stopping an apply between its commands would leave
+                        // the outputs half configured
+                        if task != Task::Apply && ui.button("Cancel").clicked() {
+                            self.worker.cancel();
+                        }
+                        ui.label(task.label());
+                        ui.spinner();
+                    } else if let Some(error) = &self.error_msg {
+                        ui.label(
+                            RichText::new(format!(
+                                "{}  {}",
+                                egui_phosphor::regular::WARNING,
+                                error.trim()
+                            ))
+                            .color(ui.visuals().error_fg_color),
+                        );
+                    }
+                });
             });
             ui.add_space(1.0);
         });
     }
 
+    pub fn render_diagnostics(&mut self, ui: &mut Ui) {
+        ui.add_space(6.0);
+        if self.backend.is_some() {
+            ui.heading("Diagnostics");
+        } else {
+            ui.heading(format!(
+                "{}  No supported display backend found",
+                egui_phosphor::regular::WARNING
+            ));
+            ui.label(
+                "WayDisplay needs one of the tools below to read and change the display configuration.",
+            );
+        }
+        ui.separator();
+
+        if let Some(diagnostics) = &self.diagnostics {
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, true])
+                .max_height(ui.available_height() - 40.0)
+                .show(ui, |ui| {
+                    for (title, probes) in [
+                        ("Tools", &diagnostics.tools),
+                        ("Protocols", &diagnostics.protocols),
+                        ("Session", &diagnostics.session),
+                    ] {
+                        ui.add_space(5.0);
+                        ui.label(RichText::new(title).strong());
+                        egui::Grid::new(title)
+                            .num_columns(3)
+                            .spacing(vec2(12.0, 6.0))
+                            .show(ui, |ui| {
+                                for probe in probes {
+                                    render_probe(ui, probe);
+                                }
+                            });
+                    }
+                });
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui
+                .button(format!(
+                    "{}  Retry",
+                    egui_phosphor::regular::ARROWS_CLOCKWISE
+                ))
+                .clicked()
+            {
+                self.retry_detection();
+            }
+            if self.backend.is_some() && ui.button("Close").clicked() {
+                self.diagnostics = None;
+            }
+        });
+    }
+
+    pub fn render_export_window(&mut self, ctx: &Context) {
+        let layout = self.layout();
+        let Some(export) = &mut self.export else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Export Layout")
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    let previous = export.format;
+                    egui::ComboBox::from_id_salt("export_format")
+                        .selected_text(export.format.label())
+                        .show_ui(ui, |ui| {
+                            for format in ExportFormat::ALL {
+                                ui.selectable_value(&mut export.format, format, format.label());
+                            }
+                        });
+                    if export.format != previous {
+                        export.path = export.format.default_path().display().to_string();
+                        export.status = None;
+                    }
+                    if export.format.has_profiles() {
+                        ui.label("Profile:");
+                        ui.text_edit_singleline(&mut export.profile_name);
+                    }
+                });
+
+                let mut text = export.format.export(&export.profile_name, &layout);
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut text)
+                                .code_editor()
+                                .desired_width(f32::INFINITY)
+                                .interactive(false),
+                        );
+                    });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(format!(
+                            "{}  Copy to Clipboard",
+                            egui_phosphor::regular::CLIPBOARD
+                        ))
+                        .clicked()
+                    {
+                        ui.ctx().copy_text(text.clone());
+                        export.status = Some(Ok("Copied to Clipboard!".to_owned()));
+                    }
+                    ui.text_edit_singleline(&mut export.path);
+                    if ui
+                        .button(format!("{}  Save", egui_phosphor::regular::FLOPPY_DISK))
+                        .clicked()
+                    {
+                        let path = formats::expand_home(&export.path);
+                        export.status = Some(
+                            export
+                                .format
+                                .save(&path, &text)
+                                .map(|()| format!("Saved to {}", path.display()))
+                                .map_err(|e| format!("Saving failed: {e}")),
+                        );
+                    }
+                });
+                ui.label(RichText::new(export.format.save_hint()).weak());
+
+                match &export.status {
+                    Some(Ok(msg)) => {
+                        ui.label(msg);
+                    }
+                    Some(Err(msg)) => {
+                        ui.label(RichText::new(msg).color(ui.visuals().error_fg_color));
+                    }
+                    None => {}
+                }
+            });
+
+        if !open {
+            self.export = None;
+        }
+    }
+
+    pub fn render_import_window(&mut self, ctx: &Context) {
+        let Some(import) = &mut self.import else {
+            return;
+        };
+
+        let mut open = true;
+        let mut path = None;
+        egui::Window::new(format!("Import {} Config", import.format.label()))
+            .id(egui::Id::new("import_window"))
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Config:");
+                    ui.text_edit_singleline(&mut import.path);
+                    if ui.button("Import").clicked() {
+                        path = Some(formats::expand_home(&import.path));
+                    }
+                });
+
+                match &import.result {
+                    Some(Ok(result)) => {
+                        ui.separator();
+                        ui.label(format!("Imported {} profile(s).", result.profiles.len()));
+                        if !result.warnings.is_empty() {
+                            ui.label("Skipped:");
+                            egui::ScrollArea::vertical()
+                                .max_height(250.0)
+                                .show(ui, |ui| {
+                                    for (line, warning) in &result.warnings {
+                                        ui.label(
+                                            RichText::new(if *line == 0 {
+                                                warning.clone()
+                                            } else {
+                                                format!("line {line}: {warning}")
+                                            })
+                                            .color(ui.visuals().warn_fg_color),
+                                        );
+                                    }
+                                });
+                        }
+                    }
+                    Some(Err(e)) => {
+                        ui.separator();
+                        ui.label(RichText::new(e).color(ui.visuals().error_fg_color));
+                    }
+                    None => {}
+                }
+            });
+
+        if let Some(path) = path {
+            let format = import.format;
+            let result = self.import_profiles(format, &path);
+            if let Some(import) = &mut self.import {
+                import.result = Some(result);
+            }
+        }
+        if !open {
+            self.import = None;
+        }
+    }
+
+    /// The `--switcher` window: a list of profiles and presets, applied with
+    /// Enter, a click, or by releasing the modifier of the hotkey that cycles it.
+    ///
+    /// egui doesn't report Super, so only Ctrl+P or Alt+P cycle and apply on
+    /// release. Bind the window to one of those to use it as a hold-and-cycle
+    /// switcher, with a Super+P binding it takes the arrow keys and Enter.
+    pub fn render_switcher(&mut self, ctx: &Context) {
+        let entries = self.switcher_entries();
+        let busy = self.worker.busy_task();
+        let Some(switcher) = &mut self.switcher else {
+            return;
+        };
+
+        // With --dry-run the apply is a test, which closes the window as well
+        if switcher.applied && busy.is_none() {
+            if self.error_msg.is_some() || matches!(self.test_result, Some(Err(_))) {
+                // Let the user pick again
+                switcher.applied = false;
+                switcher.held = false;
+            } else {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+
+        let count = entries.len();
+        let mut choose = false;
+        if !switcher.applied {
+            ctx.input(|i| {
+                let back = i.modifiers.shift;
+                // Shift only reverses the direction
+                let modifier = i.modifiers.alt || i.modifiers.ctrl || i.modifiers.command;
+                let cycle = i.key_pressed(Key::Tab) || (i.key_pressed(Key::P) && modifier);
+                if count > 0 {
+                    if i.key_pressed(Key::ArrowDown) || (cycle && !back) {
+                        switcher.selected += 1;
+                    }
+                    if i.key_pressed(Key::ArrowUp) || (cycle && back) {
+                        switcher.selected += count - 1;
+                    }
+                    switcher.selected %= count;
+                }
+
+                // Releasing the modifier before the outputs are read applies
+                // once the entries are there
+                if modifier {
+                    switcher.held = true;
+                } else if switcher.held {
+                    choose = true;
+                }
+                if i.key_released(Key::Enter) {
+                    choose = true;
+                }
+            });
+        }
+        if ctx.input(|i| i.key_pressed(Key::Escape)) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Switch Display Layout");
+            ui.separator();
+            if self.monitors.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Reading displays…");
+                });
+            }
+            egui::ScrollArea::vertical()
+                .max_height(ui.available_height() - 60.0)
+                .show(ui, |ui| {
+                    for (i, (_, label)) in entries.iter().enumerate() {
+                        let selected = switcher.selected == i;
+                        let res = ui.add_enabled(
+                            !switcher.applied,
+                            egui::Button::selectable(selected, RichText::new(label).size(16.0)),
+                        );
+                        if selected {
+                            res.scroll_to_me(None);
+                        }
+                        if res.clicked() {
+                            switcher.selected = i;
+                            choose = true;
+                        }
+                    }
+                });
+
+            ui.separator();
+            if let Some(task) = busy {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(task.label());
+                });
+            } else if let Some(error) = &self.error_msg {
+                ui.label(RichText::new(error.trim()).color(ui.visuals().error_fg_color));
+            } else if let Some(result) = &self.test_result {
+                let (msg, color) = match result {
+                    Ok(msg) => (msg, ui.visuals().text_color()),
+                    Err(msg) => (msg, ui.visuals().error_fg_color),
+                };
+                ui.label(RichText::new(msg.trim()).color(color));
+            } else {
+                ui.label(
+                    RichText::new("↑↓ or Ctrl+P to choose, Enter to apply, Esc to close").weak(),
+                );
+            }
+        });
+
+        let entry = entries.get(switcher.selected).map(|(entry, _)| *entry);
+        if choose && let Some(entry) = entry {
+            switcher.applied = true;
+            self.switch_to(entry);
+        }
+    }
+
+    pub fn render_history_window(&mut self, ctx: &Context) {
+        if !self.show_history {
+            return;
+        }
+
+        let mut open = true;
+        let mut undo_steps = 0;
+        let mut redo_steps = 0;
+        let mut roll_back = None;
+        egui::Window::new("History")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new("Edits").strong());
+                let edits = self.history.edits();
+                let undone = self.history.undone();
+                if edits.is_empty() && undone.is_empty() {
+                    ui.label(RichText::new("Nothing edited yet").weak());
+                }
+                egui::ScrollArea::vertical()
+                    .id_salt("edit_history")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for (i, snapshot) in edits.iter().enumerate() {
+                            if ui
+                                .selectable_label(false, &snapshot.label)
+                                .on_hover_text("Undo this and every later edit")
+                                .clicked()
+                            {
+                                undo_steps = edits.len() - i;
+                            }
+                        }
+                        for (i, snapshot) in undone.iter().enumerate().rev() {
+                            if ui
+                                .selectable_label(false, RichText::new(&snapshot.label).weak())
+                                .on_hover_text("Redo up to this edit")
+                                .clicked()
+                            {
+                                redo_steps = undone.len() - i;
+                            }
+                        }
+                    });
+
+                ui.separator();
+                ui.label(RichText::new("Applied This Session").strong());
+                let idle = self.worker.busy_task().is_none();
+                egui::ScrollArea::vertical()
+                    .id_salt("applied_history")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for (i, snapshot) in self.history.applied().iter().enumerate().rev() {
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .add_enabled(idle, egui::Button::new("Roll Back"))
+                                    .clicked()
+                                {
+                                    roll_back = Some(i);
+                                }
+                                ui.label(&snapshot.label);
+                                ui.label(RichText::new(format_age(snapshot.time.elapsed())).weak());
+                            });
+                        }
+                    });
+            });
+
+        for _ in 0..undo_steps {
+            self.undo();
+        }
+        for _ in 0..redo_steps {
+            self.redo();
+        }
+        if let Some(i) = roll_back {
+            self.roll_back(i);
+        }
+        if !open {
+            self.show_history = false;
+        }
+    }
+
+    pub fn render_rename_window(&mut self, ctx: &Context) {
+        let Some((id, alias)) = &mut self.renaming else {
+            return;
+        };
+
+        let mut open = true;
+        let mut save = false;
+        let mut cancel = false;
+        egui::Window::new("Rename Display")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let res = ui.add(egui::TextEdit::singleline(alias).hint_text("e.g. Left Dell"));
+                res.request_focus();
+                if res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    save = true;
+                }
+                ui.label(RichText::new("Leave empty to use the connector name.").weak());
+                ui.horizontal(|ui| {
+                    save |= ui.button("Save").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        if save {
+            let (id, alias) = (id.clone(), alias.clone());
+            self.rename_output(id, &alias);
+        }
+        if save || cancel || !open {
+            self.renaming = None;
+        }
+    }
+
     pub fn render_side_panel(&mut self, ctx: &Context) {
         egui::SidePanel::left("display_panel")
             .resizable(false)
@@ -57,8 +708,12 @@ impl WayDisplay {
                 ui.add_space(6.0);
                 ui.horizontal(|ui| {
                     ui.heading("Available Displays:");
+                    let idle = self.worker.busy_task().is_none();
                     if ui
-                        .button(egui_phosphor::regular::ARROWS_CLOCKWISE)
+                        .add_enabled(
+                            idle,
+                            egui::Button::new(egui_phosphor::regular::ARROWS_CLOCKWISE),
+                        )
                         .clicked()
                     {
                         self.refresh_monitors();
@@ -67,23 +722,40 @@ impl WayDisplay {
                 ui.separator();
 
                 ui.add_space(5.0);
+                let mut selected = None;
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     for (i, monitor) in self.monitors.iter().enumerate() {
                         let is_selected = self.selected_idx == Some(i);
-                        let icon_text = format!(
-                            "{}  {}  ({})",
-                            egui_phosphor::regular::MONITOR,
-                            monitor.name,
-                            monitor.model
-                        );
-                        if ui
-                            .selectable_label(is_selected, RichText::new(icon_text).size(16.0))
-                            .clicked()
-                        {
-                            self.selected_idx = Some(i);
+                        let icon_text = match &monitor.alias {
+                            Some(alias) => format!(
+                                "{}  {alias}  ({})",
+                                egui_phosphor::regular::MONITOR,
+                                monitor.name
+                            ),
+                            None => format!(
+                                "{}  {}  ({})",
+                                egui_phosphor::regular::MONITOR,
+                                monitor.name,
+                                monitor.model
+                            ),
+                        };
+                        let res =
+                            ui.selectable_label(is_selected, RichText::new(icon_text).size(16.0));
+                        if res.clicked() {
+                            selected = Some(i);
                         }
+                        res.context_menu(|ui| {
+                            if ui.button("Rename…").clicked() {
+                                self.renaming =
+                                    Some((monitor.id(), monitor.alias.clone().unwrap_or_default()));
+                                ui.close();
+                            }
+                        });
                     }
                 });
+                if let Some(i) = selected {
+                    self.select_monitor(i);
+                }
             });
     }
 
@@ -93,8 +765,32 @@ impl WayDisplay {
                 let monitor_name = monitor.name.clone();
                 let monitor_model = monitor.model.clone();
                 let modes = monitor.modes.clone();
+                let before = self.monitors.clone();
+                let mut edit = None;
 
-                ui.heading(format!("Display: {monitor_name} ({monitor_model})"));
+                ui.horizontal(|ui| {
+                    ui.heading(format!("Display: {} ({monitor_model})", monitor.label()));
+                    if ui
+                        .button(egui_phosphor::regular::PENCIL_SIMPLE)
+                        .on_hover_text("Rename")
+                        .clicked()
+                    {
+                        self.renaming =
+                            Some((monitor.id(), monitor.alias.clone().unwrap_or_default()));
+                    }
+                });
+                egui::CollapsingHeader::new("Display Information").show(ui, |ui| {
+                    if let Some(edid) = &monitor.edid {
+                        render_edid(ui, edid);
+                    } else {
+                        ui.label(
+                            RichText::new(format!(
+                                "No EDID found for {monitor_name} under /sys/class/drm"
+                            ))
+                            .weak(),
+                        );
+                    }
+                });
                 ui.add_space(8.0);
                 ui.separator();
 
@@ -128,6 +824,10 @@ impl WayDisplay {
                                             .clicked()
                                         {
                                             self.selected_mode_idx = Some(m_idx);
+                                            if let Some(monitor) = self.monitors.get_mut(idx) {
+                                                monitor.set_current_mode(m_idx);
+                                            }
+                                            edit = Some(format!("Mode {monitor_name}"));
                                         }
                                     }
                                 });
@@ -143,132 +843,265 @@ impl WayDisplay {
                         |ui| {
                             ui.label("Settings:");
                             ui.separator();
-                            ui.checkbox(&mut self.monitor_enabled, "Enable Monitor");
-                            ui.checkbox(&mut self.adaptive_sync, "Adaptive Sync");
-                            ui.label("Scaling:");
-                            ui.text_edit_singleline(&mut self.scaling);
+                            let mut match_scales = false;
+                            let mut new_position = None;
+                            let mut enable = false;
+                            if let Some(monitor) = self.monitors.get_mut(idx) {
+                                let mut enabled = monitor.enabled;
+                                if ui.checkbox(&mut enabled, "Enable Monitor").changed() {
+                                    // Enabling may need a mode, see `enable_output`
+                                    if enabled {
+                                        enable = true;
+                                    } else {
+                                        monitor.enabled = false;
+                                    }
+                                    let action = if enabled { "Enable" } else { "Disable" };
+                                    edit = Some(format!("{action} {monitor_name}"));
+                                }
+                                if ui
+                                    .checkbox(&mut monitor.adaptive_sync, "Adaptive Sync")
+                                    .changed()
+                                {
+                                    edit = Some(format!("Adaptive sync {monitor_name}"));
+                                }
+                                ui.label("Scaling:");
+                                if ui.text_edit_singleline(&mut self.scaling).changed()
+                                    && let Ok(scale) = self.scaling.trim().parse::<f32>()
+                                {
+                                    monitor.scale = scale;
+                                    edit = Some(format!("Scale {monitor_name}"));
+                                }
+                                if self.scaling.trim().parse::<f32>().is_err() {
+                                    ui.label(
+                                        RichText::new(format!("Invalid scale: {}", self.scaling))
+                                            .color(ui.visuals().error_fg_color),
+                                    );
+                                }
+                                if let Some(ppi) = dpi::ppi(monitor) {
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "{ppi:.0} PPI, {:.0} logical DPI",
+                                            ppi / monitor.scale
+                                        ))
+                                        .weak(),
+                                    );
+                                    let suggested = dpi::suggest_scale(ppi, self.target_dpi);
+                                    ui.horizontal(|ui| {
+                                        ui.label("Target DPI:");
+                                        ui.add(
+                                            egui::DragValue::new(&mut self.target_dpi)
+                                                .range(48.0..=288.0),
+                                        );
+                                        if ui
+                                            .add_enabled(
+                                                suggested != monitor.scale,
+                                                egui::Button::new(format!("Use {suggested}"))
+                                                    .small(),
+                                            )
+                                            .clicked()
+                                        {
+                                            monitor.scale = suggested;
+                                            self.scaling = suggested.to_string();
+                                            edit = Some(format!("Scale {monitor_name}"));
+                                        }
+                                    });
+                                    if self.is_multi_monitor
+                                        && ui
+                                            .small_button("Match text size on other displays")
+                                            .clicked()
+                                    {
+                                        match_scales = true;
+                                    }
+                                } else {
+                                    ui.label(
+                                        RichText::new("Physical size unknown, no DPI suggestion")
+                                            .weak(),
+                                    );
+                                }
+                                if self.is_multi_monitor {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Position:");
+                                        let (mut x, mut y) = (monitor.x, monitor.y);
+                                        let changed = ui
+                                            .add(egui::DragValue::new(&mut x).prefix("X "))
+                                            .changed()
+                                            | ui.add(egui::DragValue::new(&mut y).prefix("Y "))
+                                                .changed();
+                                        if changed {
+                                            new_position = Some((x, y));
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text(
+                                        "Logical pixels, arrow keys nudge the selected output",
+                                    );
+                                }
+                                ui.label("Transform:");
+                                egui::ComboBox::from_id_salt("transform")
+                                    .selected_text(monitor.transform.name())
+                                    .show_ui(ui, |ui| {
+                                        for transform in Transform::ALL {
+                                            if ui
+                                                .selectable_value(
+                                                    &mut monitor.transform,
+                                                    transform,
+                                                    transform.name(),
+                                                )
+                                                .changed()
+                                            {
+                                                edit = Some(format!("Transform {monitor_name}"));
+                                            }
+                                        }
+                                    });
+                            }
+                            if enable {
+                                self.enable_output(idx);
+                            }
+                            if let Some((x, y)) = new_position {
+                                self.move_output(idx, x, y);
+                                edit = Some(format!("Move {monitor_name}"));
+                            }
+                            if match_scales {
+                                self.match_scales(idx);
+                                edit = Some(format!("Match scales to {monitor_name}"));
+                            }
 
                             if self.is_multi_monitor {
                                 // Alignment Canvas
                                 ui.separator();
                                 ui.label("Monitor Alignment:");
-
-                                let (rect, _) = ui.allocate_at_least(
-                                    vec2(ui.available_width(), 200.0),
-                                    egui::Sense::hover(),
-                                );
-                                ui.painter()
-                                    .rect_filled(rect, 3.0, ui.visuals().extreme_bg_color);
-                                let center = rect.center();
-
-                                for i in 0..self.monitors.len() {
-                                    let preview_size = vec2(80.0, 45.0);
-                                    let monitor_rect = egui::Rect::from_center_size(
-                                        center
-                                            + self
-                                                .monitors
-                                                .get(i)
-                                                .expect("Monitor index out of bounds")
-                                                .visual_pos
-                                                .to_vec2(),
-                                        preview_size,
-                                    );
-
-                                    let id = ui.make_persistent_id(format!("mon_drag_{i}"));
-                                    let res = ui.interact(monitor_rect, id, egui::Sense::drag());
-
-                                    if res.dragged() {
-                                        // Move the monitor
-                                        if let Some(m) = self.monitors.get_mut(i) {
-                                            m.visual_pos += res.drag_delta();
-                                        }
-
-                                        // Define boundaries
-                                        let half_w = rect.width() / 2.0;
-                                        let half_h = rect.height() / 2.0;
-
-                                        let margin_x = 40.0;
-                                        let margin_y = 22.5;
-
-                                        // Clamp visual_pos relative to the center of the box
-                                        for monitor in &mut self.monitors {
-                                            monitor.visual_pos.x = monitor
-                                                .visual_pos
-                                                .x
-                                                .clamp(-half_w + margin_x, half_w - margin_x);
-
-                                            monitor.visual_pos.y = monitor
-                                                .visual_pos
-                                                .y
-                                                .clamp(-half_h + margin_y, half_h - margin_y);
+                                let mut preset = None;
+                                ui.horizontal_wrapped(|ui| {
+                                    for p in Preset::ALL {
+                                        let fits = p.layout(&self.monitors).is_some();
+                                        if ui
+                                            .add_enabled(fits, egui::Button::new(p.label()).small())
+                                            .on_disabled_hover_text(p.requirement())
+                                            .clicked()
+                                        {
+                                            preset = Some(p);
                                         }
-                                        let snap_dist = 10.0;
-                                        for j in 0..self.monitors.len() {
-                                            if i == j {
-                                                continue;
-                                            }
-                                            if let Some(other_monitor) = self.monitors.get(j) {
-                                                let target_x = other_monitor.visual_pos.x - 80.0;
-
-                                                if let Some(monitor_i) =
-                                                    self.monitors.get_mut(i).filter(|m| {
-                                                        (m.visual_pos.x - target_x).abs()
-                                                            < snap_dist
-                                                    })
-                                                {
-                                                    monitor_i.visual_pos.x = target_x;
-                                                }
+                                    }
+                                });
+                                if let Some(preset) = preset {
+                                    self.load_preset(preset);
+                                }
+                                let mut line_up = None;
+                                ui.horizontal_wrapped(|ui| {
+                                    ui.checkbox(&mut self.physical_canvas, "Physical size")
+                                        .on_hover_text(
+                                            "Draw outputs at their size on the desk and position \
+                                             them so the cursor crosses edges at the same height",
+                                        );
+                                    if self.physical_canvas {
+                                        for align in physical::Align::ALL {
+                                            if ui.small_button(align.label()).clicked() {
+                                                line_up = Some(align);
                                             }
                                         }
                                     }
-                                    let color = if self.selected_idx == Some(i) {
-                                        ui.visuals().selection.bg_fill
-                                    } else {
-                                        ui.visuals().widgets.inactive.bg_fill
-                                    };
-                                    ui.painter().rect_filled(monitor_rect, 2.0, color);
-                                    ui.painter().rect_stroke(
-                                        monitor_rect,
-                                        2.0,
-                                        ui.visuals().widgets.active.fg_stroke,
-                                        egui::StrokeKind::Middle,
-                                    );
+                                });
+                                if let Some(align) = line_up {
+                                    self.line_up(align);
+                                }
+
+                                if let Some(label) = self.render_canvas(ui) {
+                                    edit = Some(label);
                                 }
                             }
                         },
                     );
                     ui.separator();
 
-                    // Right Column: Info & Apply
+                    // Right Column: Pending Changes & Apply
                     ui.allocate_ui_with_layout(
                         vec2(col_width, available_height),
                         Layout::top_down(Align::Min),
                         |ui| {
-                            ui.label("Information:");
+                            ui.label("Pending Changes:");
                             ui.separator();
 
-                            if let Some(selected_mode) =
-                                self.selected_mode_idx.and_then(|idx| modes.get(idx))
-                            {
-                                ui.label(format!(
-                                    "Resolution: {}x{}",
-                                    selected_mode.width, selected_mode.height
-                                ));
-                                ui.label(format!("Refresh: {:.2} Hz", selected_mode.refresh));
-                                ui.label(format!("Monitor enabled: {}", self.monitor_enabled));
-                                ui.label(format!("VRR: {}", self.adaptive_sync));
-                                ui.label(format!("Scaling: {}", self.scaling));
-                                ui.add_space(10.0);
-
-                                if ui.button(RichText::new("Apply").size(14.0)).clicked() {
-                                    self.apply_settings();
+                            let changes = self.pending_changes();
+                            if changes.is_empty() {
+                                ui.label(RichText::new("Matches the active configuration").weak());
+                            }
+                            for output in &changes {
+                                ui.label(RichText::new(&output.name).strong());
+                                egui::Grid::new(("changes", &output.name))
+                                    .num_columns(2)
+                                    .show(ui, |ui| {
+                                        for change in &output.changes {
+                                            ui.label(change.setting);
+                                            ui.horizontal(|ui| {
+                                                ui.label(RichText::new(&change.live).weak());
+                                                ui.label(egui_phosphor::regular::ARROW_RIGHT);
+                                                ui.label(
+                                                    RichText::new(&change.pending)
+                                                        .color(ui.visuals().warn_fg_color),
+                                                );
+                                            });
+                                            ui.end_row();
+                                        }
+                                    });
+                            }
+                            ui.add_space(10.0);
+
+                            let idle = self.worker.busy_task().is_none();
+                            ui.horizontal(|ui| {
+                                let apply = if self.dry_run {
+                                    "Apply (Dry Run)"
+                                } else {
+                                    "Apply"
+                                };
+                                if ui
+                                    .add_enabled(
+                                        idle && !changes.is_empty(),
+                                        egui::Button::new(RichText::new(apply).size(14.0)),
+                                    )
+                                    .on_disabled_hover_text(if idle {
+                                        "Nothing to apply"
+                                    } else {
+                                        "Busy"
+                                    })
+                                    .clicked()
+                                {
+                                    self.apply_changes();
+                                }
+                                if ui
+                                    .add_enabled(
+                                        idle,
+                                        egui::Button::new(RichText::new("Test").size(14.0)),
+                                    )
+                                    .on_hover_text("Check the configuration without applying it")
+                                    .clicked()
+                                {
+                                    self.test_layout();
                                 }
+                            });
+                            match &self.test_result {
+                                Some(Ok(msg)) => {
+                                    ui.label(format!(
+                                        "{}  {msg}",
+                                        egui_phosphor::regular::CHECK_CIRCLE
+                                    ));
+                                }
+                                Some(Err(msg)) => {
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "{}  {}",
+                                            egui_phosphor::regular::X_CIRCLE,
+                                            msg.trim()
+                                        ))
+                                        .color(ui.visuals().error_fg_color),
+                                    );
+                                }
+                                None => {}
                             }
 
                             if let Some(cmd_str) = &self.cmd_output {
                                 ui.add_space(15.0);
                                 ui.separator();
-                                ui.label("wlr-randr Command:");
+                                ui.label("Command:");
                                 ui.add_space(5.0);
                                 ui.add(egui::Label::new(egui::RichText::new(cmd_str).code()));
                                 ui.add_space(15.0);
@@ -307,6 +1140,9 @@ impl WayDisplay {
                         },
                     );
                 });
+                if let Some(label) = edit {
+                    self.record_edit(label, before);
+                }
             }
         } else {
             ui.centered_and_justified(|ui| {
@@ -315,3 +1151,104 @@ impl WayDisplay {
         }
     }
 }
+
+fn render_probe(ui: &mut Ui, probe: &Probe) {
+    let (icon, color) = match probe.status {
+        ProbeStatus::Found => (
+            egui_phosphor::regular::CHECK_CIRCLE,
+            ui.visuals().text_color(),
+        ),
+        ProbeStatus::Missing => (
+            egui_phosphor::regular::X_CIRCLE,
+            ui.visuals().error_fg_color,
+        ),
+        ProbeStatus::Unknown => (egui_phosphor::regular::QUESTION, ui.visuals().warn_fg_color),
+    };
+    ui.label(RichText::new(format!("{icon}  {}", probe.name)).color(color));
+    ui.label(RichText::new(&probe.detail).code());
+    if probe.status == ProbeStatus::Found {
+        ui.label("");
+    } else {
+        ui.label(probe.hint);
+    }
+    ui.end_row();
+}
+
+/// How long ago something happened, e.g. `5 min ago`
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_owned(),
+        1..60 => format!("{minutes} min ago"),
+        _ => format!("{} h ago", minutes / 60),
+    }
+}
+
+fn render_edid(ui: &mut Ui, edid: &Edid) {
+    let name = edid.name.clone().unwrap_or_else(|| edid.texts.join(" "));
+    let serial = edid
+        .serial_text
+        .clone()
+        .or_else(|| edid.serial.map(|s| s.to_string()))
+        .unwrap_or_default();
+    let date = match edid.week {
+        _ if edid.model_year => format!("Model year {}", edid.year),
+        Some(week) => format!("Week {week}, {}", edid.year),
+        None => edid.year.to_string(),
+    };
+    let size = edid.size_cm.map_or_else(String::new, |(w, h)| {
+        let diagonal = edid
+            .diagonal_inches()
+            .map(|d| format!(" ({d:.1}\u{2033})"))
+            .unwrap_or_default();
+        format!("{w} × {h} cm{diagonal}")
+    });
+    let native = edid.native_timing.as_ref().map_or_else(String::new, |t| {
+        format!(
+            "{}x{} @ {} Hz",
+            t.width,
+            t.height,
+            formats::format_refresh(t.refresh)
+        )
+    });
+    let range = edid.range_limits.as_ref().map_or_else(String::new, |r| {
+        format!("{}–{} Hz", r.min_refresh, r.max_refresh)
+    });
+    let hdr = edid.hdr.as_ref().map_or_else(
+        || "Not supported".to_owned(),
+        |hdr| {
+            let luminance = hdr
+                .max_luminance
+                .map(|l| format!(", up to {l:.0} cd/m²"))
+                .unwrap_or_default();
+            format!("{}{luminance}", hdr.eotfs.join(", "))
+        },
+    );
+
+    egui::Grid::new("edid")
+        .num_columns(2)
+        .spacing(vec2(12.0, 4.0))
+        .show(ui, |ui| {
+            for (label, value) in [
+                (
+                    "Manufacturer",
+                    format!("{} ({})", edid.vendor(), edid.manufacturer),
+                ),
+                ("Product", format!("{name} ({:04X})", edid.product_code)),
+                ("Serial", serial),
+                ("Manufactured", date),
+                ("Size", size),
+                ("Native mode", native),
+                ("Refresh range", range),
+                ("HDR", hdr),
+                ("Colorimetry", edid.colorimetry.join(", ")),
+            ] {
+                if value.is_empty() {
+                    continue;
+                }
+                ui.label(label);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
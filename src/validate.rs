@@ -0,0 +1,155 @@
+use crate::models::Monitor;
+use std::ops::RangeInclusive;
+
+/// Scales compositors render sensibly
+const SCALES: RangeInclusive<f32> = 0.25..=4.0;
+
+/// Checks a layout for mistakes any compositor would reject, for backends that
+/// can't test a configuration themselves. Positions are only checked if
+/// `positioned`, i.e. when they will be applied.
+///
+/// Returns one message per problem, empty if the layout looks valid.
+pub fn validate(layout: &[Monitor], positioned: bool) -> Vec<String> {
+    let mut problems = Vec::new();
+    let enabled: Vec<&Monitor> = layout.iter().filter(|m| m.enabled).collect();
+    if enabled.is_empty() {
+        problems.push("At least one output has to stay enabled".to_owned());
+    }
+
+    for monitor in &enabled {
+        if monitor.current_mode().is_none() {
+            problems.push(format!("{}: no mode selected", monitor.name));
+        }
+        if !SCALES.contains(&monitor.scale) {
+            problems.push(format!(
+                "{}: scale {} is outside {}–{}",
+                monitor.name,
+                monitor.scale,
+                SCALES.start(),
+                SCALES.end()
+            ));
+        }
+    }
+
+    if positioned {
+        for (i, a) in enabled.iter().enumerate() {
+            for b in enabled.iter().skip(i + 1) {
+                if overlap(a, b) {
+                    problems.push(format!("{} and {} overlap", a.name, b.name));
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn overlap(a: &Monitor, b: &Monitor) -> bool {
+    let (Some((aw, ah)), Some((bw, bh))) = (a.logical_size(), b.logical_size()) else {
+        return false;
+    };
+    // Outputs sharing an origin mirror each other
+    if (a.x, a.y) == (b.x, b.y) {
+        return false;
+    }
+    let (ax, ay) = (a.x as f32, a.y as f32);
+    let (bx, by) = (b.x as f32, b.y as f32);
+    // Rounding in fractional scales leaves neighbours a fraction of a pixel apart or inside
+    ax + aw > bx + 0.5 && bx + bw > ax + 0.5 && ay + ah > by + 0.5 && by + bh > ay + 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32) -> Monitor {
+        Monitor {
+            x,
+            y,
+            ..Monitor::with_mode(name, (1920, 1080))
+        }
+    }
+
+    #[test]
+    fn side_by_side_is_valid() {
+        let layout = [monitor("DP-1", 0, 0), monitor("DP-2", 1920, 0)];
+        assert_eq!(validate(&layout, true), Vec::<String>::new());
+    }
+
+    #[test]
+    fn overlapping_outputs() {
+        let layout = [monitor("DP-1", 0, 0), monitor("DP-2", 1000, 200)];
+        assert_eq!(validate(&layout, true), ["DP-1 and DP-2 overlap"]);
+        assert!(
+            validate(&layout, false).is_empty(),
+            "positions aren't applied"
+        );
+
+        let mirrored = [monitor("DP-1", 0, 0), monitor("DP-2", 0, 0)];
+        assert!(
+            validate(&mirrored, true).is_empty(),
+            "mirrors share an origin"
+        );
+
+        let disabled = [
+            monitor("DP-1", 0, 0),
+            Monitor {
+                enabled: false,
+                ..monitor("DP-2", 1000, 200)
+            },
+        ];
+        assert!(
+            validate(&disabled, true).is_empty(),
+            "disabled outputs don't count"
+        );
+    }
+
+    #[test]
+    fn fractional_rounding_is_no_overlap() {
+        // 2561 / 1.5 ends a third of a pixel past DP-2's origin
+        let layout = [
+            Monitor {
+                scale: 1.5,
+                ..Monitor::with_mode("DP-1", (2561, 1440))
+            },
+            monitor("DP-2", 1707, 0),
+        ];
+        assert!(
+            validate(&layout, true).is_empty(),
+            "{:?}",
+            validate(&layout, true)
+        );
+    }
+
+    #[test]
+    fn scale_out_of_range() {
+        let layout = [Monitor {
+            scale: 5.0,
+            ..monitor("DP-1", 0, 0)
+        }];
+        assert_eq!(
+            validate(&layout, false),
+            ["DP-1: scale 5 is outside 0.25–4"]
+        );
+    }
+
+    #[test]
+    fn one_output_stays_enabled() {
+        let layout = [Monitor {
+            enabled: false,
+            ..monitor("DP-1", 0, 0)
+        }];
+        assert_eq!(
+            validate(&layout, false),
+            ["At least one output has to stay enabled"]
+        );
+    }
+
+    #[test]
+    fn enabled_output_needs_a_mode() {
+        let layout = [Monitor {
+            modes: Vec::new(),
+            ..monitor("DP-1", 0, 0)
+        }];
+        assert_eq!(validate(&layout, false), ["DP-1: no mode selected"]);
+    }
+}
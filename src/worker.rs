@@ -0,0 +1,387 @@
+use crate::backend::{Backend, command_line};
+use crate::diagnostics::Diagnostics;
+use crate::edid;
+use crate::models::{self, Monitor};
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time a backend gets to list the outputs before it is killed
+pub const LIST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time each command applying a configuration gets before it is killed
+const APPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Backend call to run on the worker thread
+pub enum Job {
+    Refresh(Backend),
+    /// Background check for output changes, not shown as progress
+    Poll(Backend),
+    /// Commands are run in order, stopping at the first failure
+    Apply(Vec<Command>),
+    /// Asks the compositor whether it would accept a configuration
+    Test(Command),
+    /// Picks a backend for the session, see [`Backend::detect`]
+    Detect,
+    /// Probes the system for the diagnostics screen
+    Diagnose,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Task {
+    Refresh,
+    Poll,
+    Apply,
+    Test,
+    Detect,
+    Diagnose,
+}
+
+impl Task {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Refresh => "Reading displays…",
+            Self::Poll => "Checking for display changes…",
+            Self::Apply => "Applying settings…",
+            Self::Test => "Testing configuration…",
+            Self::Detect => "Detecting display backend…",
+            Self::Diagnose => "Checking the system…",
+        }
+    }
+}
+
+pub enum JobResult {
+    Monitors(Result<Vec<Monitor>, String>),
+    Applied(Result<(), String>),
+    Tested(Result<(), String>),
+    /// The backend to use, `None` if no backend works in this session
+    Detected(Result<Option<Backend>, String>),
+    Diagnosed(Result<Diagnostics, String>),
+    Cancelled,
+}
+
+impl JobResult {
+    /// `task` failed with `error` before it could report a result of its own
+    fn failed(task: Task, error: String) -> Self {
+        match task {
+            Task::Refresh | Task::Poll => Self::Monitors(Err(error)),
+            Task::Apply => Self::Applied(Err(error)),
+            Task::Test => Self::Tested(Err(error)),
+            Task::Detect => Self::Detected(Err(error)),
+            Task::Diagnose => Self::Diagnosed(Err(error)),
+        }
+    }
+}
+
+struct Request {
+    id: u64,
+    job: Job,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Runs backend commands on a background thread so the UI never blocks on them
+#[derive(Default)]
+pub struct Worker {
+    jobs: Option<Sender<Request>>,
+    results: Option<Receiver<(u64, JobResult)>>,
+    next_id: u64,
+    /// Job currently in flight, results of any other job are stale
+    pending: Option<(u64, Task, Arc<AtomicBool>)>,
+    /// Job submitted while an apply was in flight, started once it is done
+    queued: Option<Job>,
+}
+
+impl Worker {
+    /// Queues `job`, cancelling whatever is still running.
+    ///
+    /// An apply in flight is never cut short, stopping between its commands
+    /// would leave the outputs half configured. `job` waits for it instead,
+    /// replacing any job already waiting, see [`Self::start_queued`].
+    pub fn submit(&mut self, job: Job) {
+        if self.busy_task() == Some(Task::Apply) {
+            self.queued = Some(job);
+            return;
+        }
+        if let Some((_, _, cancel)) = self.pending.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.start(job);
+    }
+
+    fn start(&mut self, job: Job) {
+        let task = match job {
+            Job::Refresh(_) => Task::Refresh,
+            Job::Poll(_) => Task::Poll,
+            Job::Apply(_) => Task::Apply,
+            Job::Test(_) => Task::Test,
+            Job::Detect => Task::Detect,
+            Job::Diagnose => Task::Diagnose,
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.pending = Some((id, task, Arc::clone(&cancel)));
+
+        let request = Request { id, job, cancel };
+        let request = match &self.jobs {
+            Some(jobs) => match jobs.send(request) {
+                Ok(()) => return,
+                Err(err) => err.0,
+            },
+            None => request,
+        };
+
+        // First job, or the thread died: start a fresh worker
+        let (job_tx, job_rx) = channel();
+        let (result_tx, result_rx) = channel();
+        thread::spawn(move || run_worker(&job_rx, &result_tx));
+        if job_tx.send(request).is_err() {
+            log::error!("Failed to start the worker thread");
+        }
+        self.jobs = Some(job_tx);
+        self.results = Some(result_rx);
+    }
+
+    /// Starts the job that waited for an apply once nothing runs anymore, so
+    /// whatever the caller submitted in response to the apply goes first
+    pub fn start_queued(&mut self) {
+        if self.pending.is_none()
+            && let Some(job) = self.queued.take()
+        {
+            self.start(job);
+        }
+    }
+
+    /// Stops the job in flight, killing its process, and drops the one waiting for it.
+    ///
+    /// Unlike [`Self::submit`] this cuts an apply short, only offer it for other jobs.
+    pub fn cancel(&mut self) {
+        self.queued = None;
+        if let Some((_, _, cancel)) = self.pending.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Job the user is waiting for, background polls are not reported
+    pub fn busy_task(&self) -> Option<Task> {
+        self.pending
+            .as_ref()
+            .map(|(_, task, _)| *task)
+            .filter(|task| *task != Task::Poll)
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_none() && self.queued.is_none()
+    }
+
+    /// Returns the result of the job in flight once it has finished
+    pub fn poll(&mut self) -> Option<(Task, JobResult)> {
+        let results = self.results.as_ref()?;
+        loop {
+            match results.try_recv() {
+                Ok((id, result)) => {
+                    if let Some((_, task, _)) = self.pending.take_if(|(p, _, _)| *p == id) {
+                        return Some((task, result));
+                    }
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    self.jobs = None;
+                    self.results = None;
+                    return self.pending.take().map(|(_, task, _)| {
+                        let error = "Worker thread stopped".to_owned();
+                        (task, JobResult::failed(task, error))
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn run_worker(jobs: &Receiver<Request>, results: &Sender<(u64, JobResult)>) {
+    while let Ok(Request { id, job, cancel }) = jobs.recv() {
+        let result = match job {
+            Job::Refresh(backend) | Job::Poll(backend) => JobResult::Monitors(
+                run_command(backend.list_command(), LIST_TIMEOUT, &cancel)
+                    .and_then(|out| backend.parse_monitors(&String::from_utf8_lossy(&out.stdout)))
+                    .map(|mut monitors| {
+                        for m in &mut monitors {
+                            m.edid = edid::read(&m.name);
+                            m.edid_hash = m.edid.as_ref().map(edid::Edid::hash);
+                        }
+                        models::mark_shared_ids(&mut monitors);
+                        monitors
+                    }),
+            ),
+            Job::Apply(commands) => JobResult::Applied(
+                commands
+                    .into_iter()
+                    .try_for_each(|cmd| run_command(cmd, APPLY_TIMEOUT, &cancel).map(drop)),
+            ),
+            Job::Test(cmd) => JobResult::Tested(run_command(cmd, APPLY_TIMEOUT, &cancel).map(drop)),
+            Job::Detect => JobResult::Detected(Ok(Backend::detect(&cancel))),
+            Job::Diagnose => JobResult::Diagnosed(Ok(Diagnostics::probe(&cancel))),
+        };
+        let result = if cancel.load(Ordering::Relaxed) {
+            JobResult::Cancelled
+        } else {
+            result
+        };
+        if results.send((id, result)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Runs `cmd` to completion, killing it on timeout or cancellation.
+///
+/// A command exiting unsuccessfully is an error carrying its stderr.
+pub fn run_command(
+    mut cmd: Command,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<Output, String> {
+    let cmd_line = command_line(&cmd);
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("System Error: {e}"))?;
+
+    // Drain the pipes while waiting so a chatty process can't block on a full pipe
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        let cancelled = cancel.load(Ordering::Relaxed);
+        if cancelled || Instant::now() >= deadline {
+            if let Err(e) = child.kill() {
+                log::warn!("Failed to kill `{cmd_line}`: {e}");
+            }
+            // Reap the process, its exit status no longer matters
+            child.wait().ok();
+            return Err(if cancelled {
+                "Cancelled".to_owned()
+            } else {
+                format!("`{cmd_line}` timed out after {}s", timeout.as_secs())
+            });
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let output = Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}
+
+fn read_to_end(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut buf).ok();
+        }
+        buf
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sleep(secs: &str) -> Command {
+        let mut cmd = Command::new("sleep");
+        cmd.arg(secs);
+        cmd
+    }
+
+    /// Polls `worker` until the job in flight has finished
+    fn wait(worker: &mut Worker) -> (Task, JobResult) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if let Some(result) = worker.poll() {
+                return result;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("the job did not finish");
+    }
+
+    #[test]
+    fn kills_a_hung_process() {
+        let start = Instant::now();
+        let result = run_command(
+            sleep("10"),
+            Duration::from_millis(100),
+            &AtomicBool::new(false),
+        );
+        assert!(
+            result.is_err_and(|e| e.contains("`sleep 10` timed out")),
+            "a hung command is an error"
+        );
+        assert!(start.elapsed() < Duration::from_secs(5), "killed in time");
+    }
+
+    #[test]
+    fn cancelling_kills_the_process() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            flag.store(true, Ordering::Relaxed);
+        });
+        let result = run_command(sleep("10"), Duration::from_secs(20), &cancel);
+        assert_eq!(result.map(drop), Err("Cancelled".to_owned()));
+    }
+
+    #[test]
+    fn jobs_submitted_during_an_apply_wait_for_it() {
+        let mut worker = Worker::default();
+        worker.submit(Job::Apply(vec![sleep("0.2")]));
+        worker.submit(Job::Test(Command::new("false")));
+        assert_eq!(
+            worker.busy_task(),
+            Some(Task::Apply),
+            "the apply keeps running"
+        );
+
+        let (task, result) = wait(&mut worker);
+        assert_eq!(task, Task::Apply);
+        assert!(
+            matches!(result, JobResult::Applied(Ok(()))),
+            "the apply finished"
+        );
+
+        // What the caller submits in response to the apply runs first
+        worker.submit(Job::Test(sleep("0.1")));
+        worker.start_queued();
+        let (_, result) = wait(&mut worker);
+        assert!(
+            matches!(result, JobResult::Tested(Ok(()))),
+            "the follow-up ran"
+        );
+
+        worker.start_queued();
+        let (task, result) = wait(&mut worker);
+        assert_eq!(task, Task::Test);
+        assert!(
+            matches!(result, JobResult::Tested(Err(_))),
+            "the queued job ran"
+        );
+        assert!(worker.is_idle());
+    }
+}